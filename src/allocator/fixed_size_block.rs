@@ -1,6 +1,7 @@
 use super::Locked;
 use alloc::alloc::{GlobalAlloc, Layout};
 use core::{mem, ptr, ptr::NonNull};
+use x86_64::instructions::interrupts;
 
 const BLOCK_SIZES: &[usize] = &[8, 16, 32, 64, 128, 256, 512, 1024, 2048];
 // don't define any block sizes smaller that 8 because each block must be capable of storing a 64-bit pointer
@@ -12,6 +13,7 @@ struct ListNode {
 pub struct FixedSizeBlockAllocator {
     list_heads: [Option<&'static mut ListNode>; BLOCK_SIZES.len()],
     fallback_allocator: linked_list_allocator::Heap,
+    used: usize,                    // the bytes handed out and not given back, blocks counted whole
 }
 
 impl FixedSizeBlockAllocator {
@@ -20,9 +22,14 @@ impl FixedSizeBlockAllocator {
         FixedSizeBlockAllocator {
             list_heads: [EMPTY; BLOCK_SIZES.len()],
             fallback_allocator: linked_list_allocator::Heap::empty(),
+            used: 0,
         }
     }
 
+    pub fn used(&self) -> usize {
+        self.used
+    }
+
     pub unsafe fn init(&mut self, heap_start: usize, heap_size: usize) {
         self.fallback_allocator.init(heap_start, heap_size);
     }
@@ -40,50 +47,66 @@ fn list_index(layout: &Layout) -> Option<usize> {
     BLOCK_SIZES.iter().position(|&s| s >= required_block_size) // return the index in the BLOCK_SIZES slice
 }
 
+fn used_size(layout: &Layout) -> usize {    // what an allocation takes from the heap
+    match list_index(layout) {
+        Some(index) => BLOCK_SIZES[index],
+        None => layout.size(),
+    }
+}
+
 unsafe impl GlobalAlloc for Locked<FixedSizeBlockAllocator> {
     unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
-        let mut allocator = self.lock();
-        match list_index(&layout) { // judge if the index is in the range of BLOCK_SIZES
-            Some(index) => {
-                match allocator.list_heads[index].take() {  // judge if the list of the smallest size isn't empty
-                    Some(node) => {
-                        allocator.list_heads[index] = node.next.take(); // relink the list
-                        node as *mut ListNode as *mut u8                // return the node as a *mut u8 pointer
-                    }
-                    None => {   // the list of blocks is empty
-                        let block_size = BLOCK_SIZES[index];
-                        let block_align = block_size;
+        interrupts::without_interrupts(|| {     // a thread must not be switched out holding the lock, see 'task::thread'
+            let mut allocator = self.lock();
+            let ptr = match list_index(&layout) { // judge if the index is in the range of BLOCK_SIZES
+                Some(index) => {
+                    match allocator.list_heads[index].take() {  // judge if the list of the smallest size isn't empty
+                        Some(node) => {
+                            allocator.list_heads[index] = node.next.take(); // relink the list
+                            node as *mut ListNode as *mut u8                // return the node as a *mut u8 pointer
+                        }
+                        None => {   // the list of blocks is empty
+                            let block_size = BLOCK_SIZES[index];
+                            let block_align = block_size;
 
-                        // create a new Layout, because the block will be added to the block list on deallocation
-                        let layout = Layout::from_size_align(block_size, block_align).unwrap(); 
+                            // create a new Layout, because the block will be added to the block list on deallocation
+                            let layout = Layout::from_size_align(block_size, block_align).unwrap(); 
 
-                        allocator.fallback_alloc(layout)    // perform the allocation
+                            allocator.fallback_alloc(layout)    // perform the allocation
+                        }
                     }
                 }
+                None => allocator.fallback_alloc(layout),
+            };
+            if !ptr.is_null() {
+                allocator.used += used_size(&layout);
             }
-            None => allocator.fallback_alloc(layout),
-        }
+            ptr
+        })
     }
 
     unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
-        let mut allocator = self.lock();
-        match list_index(&layout) {
-            Some(index) => {
-                let new_node = ListNode {
-                    next: allocator.list_heads[index].take(),
-                };
+        interrupts::without_interrupts(|| {
+            let mut allocator = self.lock();
+            allocator.used -= used_size(&layout);
+            match list_index(&layout) {
+                Some(index) => {
+                    let new_node = ListNode {
+                        next: allocator.list_heads[index].take(),
+                    };
 
-                assert!(mem::size_of::<ListNode>() <= BLOCK_SIZES[index]);
-                assert!(mem::align_of::<ListNode>() <= BLOCK_SIZES[index]);
+                    assert!(mem::size_of::<ListNode>() <= BLOCK_SIZES[index]);
+                    assert!(mem::align_of::<ListNode>() <= BLOCK_SIZES[index]);
 
-                let new_node_ptr = ptr as *mut ListNode;
-                new_node_ptr.write(new_node);
-                allocator.list_heads[index] = Some(&mut *new_node_ptr);
-            }
-            None => {
-                let ptr = NonNull::new(ptr).unwrap();
-                allocator.fallback_allocator.deallocate(ptr, layout);
+                    let new_node_ptr = ptr as *mut ListNode;
+                    new_node_ptr.write(new_node);
+                    allocator.list_heads[index] = Some(&mut *new_node_ptr);
+                }
+                None => {
+                    let ptr = NonNull::new(ptr).unwrap();
+                    allocator.fallback_allocator.deallocate(ptr, layout);
+                }
             }
-        }
+        })
     }
 }
\ No newline at end of file
@@ -55,13 +55,17 @@ pub fn init_heap(
         };
     }
 
-    unsafe {    // initialize the heap after mapping the heap pages since the init() tries to write to the heap
+    x86_64::instructions::interrupts::without_interrupts(|| unsafe {  // initialize the heap after mapping the heap pages since the init() tries to write to the heap
         ALLOCATOR.lock().init(HEAP_START, HEAP_SIZE);
-    }
+    });
 
     Ok(())
 }
 
+pub fn heap_used() -> usize {       // the bytes of the heap in use
+    x86_64::instructions::interrupts::without_interrupts(|| ALLOCATOR.lock().used())   // as 'alloc' takes it, see 'task::thread'
+}
+
 pub struct Locked<A> {      // a wrapper around a spin::Mutex<A>
     inner: spin::Mutex<A>,
 }
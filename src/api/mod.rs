@@ -77,4 +77,20 @@ pub fn code2stringvec(char_vec: Vec<Vec<char>>) -> Vec<String> {
     }
 
     stringvec
-}
\ No newline at end of file
+}
+
+pub fn lines2bytes(lines: &Vec<Vec<char>>) -> Option<Vec<u8>> {    // a document as bytes, None if a char is not a byte
+    let mut bytes = Vec::new();
+    for (i, line) in lines.iter().enumerate() {
+        if i > 0 {
+            bytes.push(b'\n');
+        }
+        for c in line {
+            if *c as u32 > 0xff {
+                return None;
+            }
+            bytes.push(*c as u8);
+        }
+    }
+    Some(bytes)
+}
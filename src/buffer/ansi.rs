@@ -0,0 +1,367 @@
+/*
+The VT100/ANSI escape sequences the writers understand, so that the output of a program
+looks the same on the VGA and on a terminal at the other end of the serial port.
+    ESC [ n m           SGR, the colors: 0 reset, 1 bright, 7 inverse, 30-37 39 90-97 foreground, 40-47 49 100-107 background
+    ESC [ row ; col H   CUP, move the cursor, counted from 1 below the title row ('f' does the same)
+    ESC [ n A/B         move the cursor up and down
+    ESC [ n C/D         move it right and left, going on to the next or previous row at the edges like a long command line
+    ESC [ n J           ED, erase below the cursor (0), above it (1), the screen (2) or the scrollback (3)
+    ESC [ n K           EL, erase the line after the cursor (0), before it (1) or all of it (2)
+    ESC [ s, ESC 7      save the cursor
+    ESC [ u, ESC 8      restore it
+Anything else starting with ESC is swallowed. Of the other control bytes the writers know
+'\n', '\r', the backspace and the tab, which goes on to the next column multiple of 8.
+*/
+
+use super::cp437::{self, Utf8};
+
+pub const DEFAULT_COLOR: u8 = 0x0e;     // yellow on black, what 'ESC [ 0 m' goes back to
+const TAB: usize = 8;
+
+const MAX_PARAMS: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Csi {                    // a complete 'ESC [ ... final' sequence
+    pub command: u8,
+    params: [u16; MAX_PARAMS],
+    count: usize,
+}
+
+impl Csi {
+    pub fn param(&self, i: usize, default: usize) -> usize {    // a missing or 0 parameter means the default
+        if i < self.count && self.params[i] != 0 {
+            self.params[i] as usize
+        } else {
+            default
+        }
+    }
+
+    pub fn params(&self) -> &[u16] {
+        &self.params[..self.count]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Action {
+    Print(u8),                      // for the writer, a character or a control byte
+    Csi(Csi),
+    SaveCursor,
+    RestoreCursor,
+    None,                           // inside a sequence
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum State {
+    Ground,
+    Escape,                         // after ESC
+    Csi,                            // after ESC [
+}
+
+pub struct Parser {
+    state: State,
+    params: [u16; MAX_PARAMS],
+    count: usize,
+    private: bool,                  // 'ESC [ ?', the private sequences of some terminals, ignored
+}
+
+impl Parser {
+    pub const fn new() -> Parser {
+        Parser {
+            state: State::Ground,
+            params: [0; MAX_PARAMS],
+            count: 0,
+            private: false,
+        }
+    }
+
+    pub fn advance(&mut self, byte: u8) -> Action {
+        match self.state {
+            State::Ground => {
+                if byte == 0x1b {
+                    self.state = State::Escape;
+                    return Action::None;
+                }
+                Action::Print(byte)
+            },
+            State::Escape => {
+                self.state = State::Ground;
+                match byte {
+                    b'[' => {
+                        self.state = State::Csi;
+                        self.params = [0; MAX_PARAMS];
+                        self.count = 0;
+                        self.private = false;
+                        Action::None
+                    },
+                    b'7' => Action::SaveCursor,
+                    b'8' => Action::RestoreCursor,
+                    _ => Action::None,
+                }
+            },
+            State::Csi => {
+                match byte {
+                    b'0'..=b'9' => {
+                        if self.count == 0 {
+                            self.count = 1;
+                        }
+                        if self.count <= MAX_PARAMS {
+                            let param = &mut self.params[self.count - 1];
+                            *param = param.saturating_mul(10).saturating_add((byte - b'0') as u16);
+                        }
+                        Action::None
+                    },
+                    b';' => {
+                        if self.count == 0 {
+                            self.count = 1;         // ';5' has an empty first parameter
+                        }
+                        self.count += 1;
+                        Action::None
+                    },
+                    b'?' | b'<' | b'=' | b'>' => {
+                        self.private = true;
+                        Action::None
+                    },
+                    0x20..=0x3f => Action::None,   // intermediate bytes
+                    0x40..=0x7e => {
+                        self.state = State::Ground;
+                        if self.private {
+                            return Action::None;
+                        }
+                        let csi = Csi { command: byte, params: self.params, count: self.count.min(MAX_PARAMS) };
+                        match byte {
+                            b's' => Action::SaveCursor,
+                            b'u' => Action::RestoreCursor,
+                            _ => Action::Csi(csi),
+                        }
+                    },
+                    _ => {                          // not a sequence after all
+                        self.state = State::Ground;
+                        Action::None
+                    },
+                }
+            },
+        }
+    }
+}
+
+/*
+A grid of cells the text goes to, the VGA or the screen of a session.
+'write' reads the escape sequences and the UTF-8 for both in the same way,
+a screen only tells how to draw a cell, end a line and erase cells.
+Row 0 is the title and stays out of reach of the sequences.
+*/
+pub trait Screen {
+    fn size(&self) -> (usize, usize);               // the columns and the rows, counting the title row
+    fn cursor(&self) -> (usize, usize);             // the column, the width when the next character wraps, and the row
+    fn set_cursor(&mut self, col: usize, row: usize);
+    fn hide_cursor(&mut self);                      // before the cursor moves
+    fn color(&self) -> u8;
+    fn set_color(&mut self, color: u8);
+    fn saved(&mut self) -> &mut (usize, usize);     // the cursor saved by 'ESC 7'
+    fn decoder(&mut self) -> (&mut Parser, &mut Utf8);
+    fn put(&mut self, glyph: u8);                   // draw at the cursor and move on, wrapping and scrolling
+    fn new_line(&mut self);
+    fn backspace(&mut self);
+    fn erase_cells(&mut self, row: usize, from: usize, to: usize);
+    fn clear_scrollback(&mut self) {}
+}
+
+pub fn write<S: Screen>(screen: &mut S, s: &str) {
+    for byte in s.bytes() {
+        let byte = match screen.decoder().0.advance(byte) {
+            Action::Print(byte) => byte,
+            Action::Csi(csi) => {
+                escape(screen, csi);
+                continue;
+            },
+            Action::SaveCursor => {
+                let cursor = screen.cursor();
+                *screen.saved() = cursor;
+                continue;
+            },
+            Action::RestoreCursor => {
+                screen.hide_cursor();
+                let (col, row) = *screen.saved();
+                screen.set_cursor(col, row);
+                continue;
+            },
+            Action::None => continue,
+        };
+        match byte {
+            0x20..=0x7e => screen.put(byte),
+            b'\n' => screen.new_line(),
+            b'\r' => {                                  // back to the start of the line
+                screen.hide_cursor();
+                let (_, row) = screen.cursor();
+                screen.set_cursor(0, row);
+            },
+            0x08 => screen.backspace(),
+            b'\t' => {
+                let (col, _) = screen.cursor();
+                let stop = ((col / TAB + 1) * TAB).min(screen.size().0);
+                for _ in col..stop {
+                    screen.put(b' ');
+                }
+            },
+            0x80..=0xff => {                            // UTF-8
+                if let Some(c) = screen.decoder().1.advance(byte) {
+                    screen.put(cp437::glyph(c));
+                }
+            },
+            _ => screen.put(cp437::FALLBACK),          // the other control bytes
+        }
+    }
+}
+
+fn escape<S: Screen>(screen: &mut S, csi: Csi) {
+    screen.hide_cursor();
+    let n = csi.param(0, 1);
+    let (width, height) = screen.size();
+    let (x, y) = screen.cursor();
+    let col = x.min(width - 1);
+    match csi.command {
+        b'm' => {
+            let color = sgr(&csi, screen.color(), DEFAULT_COLOR);
+            screen.set_color(color);
+        },
+        b'H' | b'f' => screen.set_cursor(csi.param(1, 1).min(width) - 1, csi.param(0, 1).min(height - 1)),
+        b'A' => screen.set_cursor(x, y.saturating_sub(n).max(1)),
+        b'B' => screen.set_cursor(x, (y + n).min(height - 1)),
+        b'C' => {
+            let (mut x, mut y) = (x, y);
+            for _ in 0..n {
+                if x < width {                          // the width stands for the start of the next row
+                    x += 1;
+                } else if y < height - 1 {
+                    x = 1;
+                    y += 1;
+                }
+            }
+            screen.set_cursor(x, y);
+        },
+        b'D' => {
+            let (mut x, mut y) = (x, y);
+            for _ in 0..n {
+                if x > 0 {
+                    x -= 1;
+                } else if y > 1 {
+                    x = width - 1;
+                    y -= 1;
+                }
+            }
+            screen.set_cursor(x, y);
+        },
+        b'J' => {
+            let rows = match csi.param(0, 0) {
+                0 => {
+                    screen.erase_cells(y, x.min(width), width);    // nothing left on a full row
+                    y + 1..height
+                },
+                1 => {
+                    screen.erase_cells(y, 0, col + 1);
+                    1..y
+                },
+                2 => 1..height,
+                3 => {
+                    screen.clear_scrollback();
+                    0..0
+                },
+                _ => 0..0,
+            };
+            for row in rows {
+                screen.erase_cells(row, 0, width);
+            }
+        },
+        b'K' => match csi.param(0, 0) {
+            0 => screen.erase_cells(y, x.min(width), width),
+            1 => screen.erase_cells(y, 0, col + 1),
+            2 => screen.erase_cells(y, 0, width),
+            _ => {}
+        },
+        _ => {}
+    }
+}
+
+const COLORS: [u8; 8] = [0, 4, 2, 6, 1, 5, 3, 7];  // the VGA color of ANSI black, red, green, yellow, blue, magenta, cyan, white
+
+/*
+The color byte of the VGA, background in the high nibble, after an SGR sequence.
+'default' is what 'ESC [ 0 m' goes back to.
+*/
+pub fn sgr(csi: &Csi, color: u8, default: u8) -> u8 {
+    let mut foreground = color & 0x0f;
+    let mut background = color >> 4;
+    let mut bright = false;                 // '1' also brightens a color set after it
+    if csi.params().len() == 0 {            // 'ESC [ m'
+        return default;
+    }
+    for param in csi.params() {
+        match *param {
+            0 => {
+                foreground = default & 0x0f;
+                background = default >> 4;
+            },
+            1 => bright = true,
+            7 => core::mem::swap(&mut foreground, &mut background),
+            22 => foreground &= 0x07,
+            30..=37 => foreground = COLORS[(*param - 30) as usize],
+            39 => foreground = default & 0x0f,
+            40..=47 => background = COLORS[(*param - 40) as usize],
+            49 => background = default >> 4,
+            90..=97 => foreground = COLORS[(*param - 90) as usize] | 0x08,
+            100..=107 => background = COLORS[(*param - 100) as usize] | 0x08,
+            _ => {}
+        }
+    }
+    if bright {
+        foreground |= 0x08;
+    }
+    background << 4 | foreground
+}
+
+#[test_case]
+fn test_parse_csi() {
+    let mut parser = Parser::new();
+    let mut actions = alloc::vec::Vec::new();
+    for byte in b"a\x1b[12;5Hb\x1b[?25l\x1b7\x1b[K".iter() {
+        match parser.advance(*byte) {
+            Action::None => {},
+            action => actions.push(action),
+        }
+    }
+    assert_eq!(actions.len(), 5);
+    assert_eq!(actions[0], Action::Print(b'a'));
+    match actions[1] {
+        Action::Csi(csi) => {
+            assert_eq!(csi.command, b'H');
+            assert_eq!((csi.param(0, 1), csi.param(1, 1), csi.param(2, 1)), (12, 5, 1));
+        },
+        _ => panic!("not a CSI sequence"),
+    }
+    assert_eq!(actions[2], Action::Print(b'b'));
+    assert_eq!(actions[3], Action::SaveCursor);
+    match actions[4] {
+        Action::Csi(csi) => assert_eq!((csi.command, csi.param(0, 0)), (b'K', 0)),
+        _ => panic!("not a CSI sequence"),
+    }
+}
+
+#[test_case]
+fn test_sgr_colors() {
+    let default = 0x0e;                     // yellow on black
+    let mut parser = Parser::new();
+    let mut csi = |text: &[u8]| {
+        let mut last = Action::None;
+        for byte in text {
+            last = parser.advance(*byte);
+        }
+        match last {
+            Action::Csi(csi) => csi,
+            _ => panic!("not a CSI sequence"),
+        }
+    };
+    assert_eq!(sgr(&csi(b"\x1b[31m"), default, default), 0x04);
+    assert_eq!(sgr(&csi(b"\x1b[1;32;44m"), default, default), 0x1a);
+    assert_eq!(sgr(&csi(b"\x1b[0m"), 0x1a, default), default);
+    assert_eq!(sgr(&csi(b"\x1b[m"), 0x1a, default), default);
+}
\ No newline at end of file
@@ -0,0 +1,96 @@
+/*
+The VGA text mode draws code page 437, the writers get UTF-8: 'Utf8' puts the bytes back
+together into code points and 'glyph' picks the CP437 character drawn for each.
+What CP437 lacks is drawn as FALLBACK.
+*/
+
+pub const FALLBACK: u8 = 0xfe;      // '■'
+
+const HIGH: [char; 128] = [         // 0x80 to 0xff
+    'Ç', 'ü', 'é', 'â', 'ä', 'à', 'å', 'ç', 'ê', 'ë', 'è', 'ï', 'î', 'ì', 'Ä', 'Å',
+    'É', 'æ', 'Æ', 'ô', 'ö', 'ò', 'û', 'ù', 'ÿ', 'Ö', 'Ü', '¢', '£', '¥', '₧', 'ƒ',
+    'á', 'í', 'ó', 'ú', 'ñ', 'Ñ', 'ª', 'º', '¿', '⌐', '¬', '½', '¼', '¡', '«', '»',
+    '░', '▒', '▓', '│', '┤', '╡', '╢', '╖', '╕', '╣', '║', '╗', '╝', '╜', '╛', '┐',
+    '└', '┴', '┬', '├', '─', '┼', '╞', '╟', '╚', '╔', '╩', '╦', '╠', '═', '╬', '╧',
+    '╨', '╤', '╥', '╙', '╘', '╒', '╓', '╫', '╪', '┘', '┌', '█', '▄', '▌', '▐', '▀',
+    'α', 'ß', 'Γ', 'π', 'Σ', 'σ', 'µ', 'τ', 'Φ', 'Θ', 'Ω', 'δ', '∞', 'φ', 'ε', '∩',
+    '≡', '±', '≥', '≤', '⌠', '⌡', '÷', '≈', '°', '∙', '·', '√', 'ⁿ', '²', '■', '\u{a0}',
+];
+
+/* the glyphs whose bytes aren't control bytes for the writers, see 'ansi::write' */
+const LOW: [(char, u8); 27] = [
+    ('☺', 0x01), ('♫', 0x0e), ('☼', 0x0f), ('☻', 0x02), ('♥', 0x03), ('♦', 0x04), ('♣', 0x05), ('♠', 0x06), ('•', 0x07), ('♂', 0x0b), ('♀', 0x0c),
+    ('►', 0x10), ('◄', 0x11), ('↕', 0x12), ('‼', 0x13), ('¶', 0x14), ('§', 0x15), ('▬', 0x16), ('↨', 0x17),
+    ('↑', 0x18), ('↓', 0x19), ('→', 0x1a), ('←', 0x1b), ('∟', 0x1c), ('↔', 0x1d), ('▲', 0x1e), ('▼', 0x1f),
+];
+
+const ALIKE: [(char, char); 14] = [ // drawn as a character close enough
+    ('β', 'ß'), ('μ', 'µ'), ('∑', 'Σ'), ('∈', 'ε'),
+    ('╭', '┌'), ('╮', '┐'), ('╯', '┘'), ('╰', '└'),
+    ('‘', '\''), ('’', '\''), ('“', '"'), ('”', '"'), ('–', '-'), ('—', '-'),
+];
+
+pub fn glyph(c: char) -> u8 {
+    if c >= ' ' && c <= '~' {
+        return c as u8;
+    }
+    if let Some(i) = HIGH.iter().position(|g| *g == c) {
+        return 0x80 + i as u8;
+    }
+    if let Some((_, byte)) = LOW.iter().find(|(g, _)| *g == c) {
+        return *byte;
+    }
+    match ALIKE.iter().find(|(g, _)| *g == c) {
+        Some((_, alike)) => glyph(*alike),
+        None => FALLBACK,
+    }
+}
+
+pub fn to_char(byte: u8) -> char {  // back from the VGA, for copying the screen
+    match byte {
+        0x80..=0xff => HIGH[(byte - 0x80) as usize],
+        _ => match LOW.iter().find(|(_, b)| *b == byte) {
+            Some((c, _)) => *c,
+            None => byte as char,
+        },
+    }
+}
+
+pub struct Utf8 {                   // the bytes of a code point seen so far
+    code: u32,
+    remaining: usize,
+}
+
+impl Utf8 {
+    pub const fn new() -> Utf8 {
+        Utf8 { code: 0, remaining: 0 }
+    }
+
+    pub fn advance(&mut self, byte: u8) -> Option<char> {   // a whole code point, or None in the middle of one
+        let (code, remaining) = match byte {
+            0x00..=0x7f => (byte as u32, 0),
+            0x80..=0xbf if self.remaining > 0 => (self.code << 6 | (byte & 0x3f) as u32, self.remaining - 1),
+            0xc2..=0xdf => ((byte & 0x1f) as u32, 1),
+            0xe0..=0xef => ((byte & 0x0f) as u32, 2),
+            0xf0..=0xf4 => ((byte & 0x07) as u32, 3),
+            _ => {                                  // not where a code point can start
+                self.remaining = 0;
+                return Some(core::char::REPLACEMENT_CHARACTER);
+            }
+        };
+        self.code = code;
+        self.remaining = remaining;
+        if remaining > 0 {
+            return None;
+        }
+        Some(core::char::from_u32(code).unwrap_or(core::char::REPLACEMENT_CHARACTER))
+    }
+}
+
+#[test_case]
+fn test_utf8_to_cp437() {
+    let mut utf8 = Utf8::new();
+    let glyphs: alloc::vec::Vec<u8> = "a├─é§€".bytes().filter_map(|byte| utf8.advance(byte)).map(glyph).collect();
+    assert_eq!(glyphs, [b'a', 0xc3, 0xc4, 0x82, 0x15, FALLBACK]);
+    assert_eq!(to_char(0xc3), '├');
+}
\ No newline at end of file
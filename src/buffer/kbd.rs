@@ -0,0 +1,264 @@
+use pc_keyboard::{layouts, DecodedKey, KeyCode, KeyState, HandleControl, Keyboard, KeyboardLayout, Modifiers, ScancodeSet1};
+use x86_64::instructions::port::Port;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Layout {
+    Us,
+    Uk,
+    Dvorak,
+    Azerty,
+    German,
+}
+
+impl Layout {
+    pub const ALL: [Layout; 5] = [Layout::Us, Layout::Uk, Layout::Dvorak, Layout::Azerty, Layout::German];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Layout::Us => "us",
+            Layout::Uk => "uk",
+            Layout::Dvorak => "dvorak",
+            Layout::Azerty => "azerty",
+            Layout::German => "de",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Layout> {
+        Layout::ALL.iter().find(|layout| layout.name() == name).copied()
+    }
+}
+
+static mut LAYOUT: Layout = Layout::Us;     // the same for all the terminals
+static mut CAPS_LOCK: bool = false;         // the locks are kept here rather than by each Keyboard, which only sees
+static mut NUM_LOCK: bool = false;          // the keys of its own terminal, and shown on the LEDs
+static mut SCROLL_LOCK: bool = false;
+static mut REPEAT: (usize, usize) = (500, 11);  // the delay in ms before a held key repeats, and the repeats a second
+
+pub fn layout() -> Layout {
+    unsafe { LAYOUT }
+}
+
+pub fn set_layout(layout: Layout) {
+    unsafe {
+        LAYOUT = layout;
+    }
+}
+
+pub fn locks() -> (bool, bool, bool) {      // caps, num and scroll lock
+    unsafe { (CAPS_LOCK, NUM_LOCK, SCROLL_LOCK) }
+}
+
+pub fn repeat() -> (usize, usize) {
+    unsafe { REPEAT }
+}
+
+/*
+The layout every Keyboard is made with: it maps with the layout chosen by 'kbd'
+and the locks kept here, whichever terminal reads the key.
+*/
+pub struct AnyLayout;
+
+impl KeyboardLayout for AnyLayout {
+    fn map_keycode(keycode: KeyCode, modifiers: &Modifiers, handle_ctrl: HandleControl) -> DecodedKey {
+        let modifiers = Modifiers {
+            capslock: unsafe { CAPS_LOCK },
+            numlock: unsafe { NUM_LOCK },
+            ..*modifiers
+        };
+        match unsafe { LAYOUT } {
+            Layout::Us => layouts::Us104Key::map_keycode(keycode, &modifiers, handle_ctrl),
+            Layout::Uk => layouts::Uk105Key::map_keycode(keycode, &modifiers, handle_ctrl),
+            Layout::Dvorak => layouts::Dvorak104Key::map_keycode(keycode, &modifiers, handle_ctrl),
+            Layout::Azerty => layouts::Azerty::map_keycode(keycode, &modifiers, handle_ctrl),
+            Layout::German => De105Key::map_keycode(keycode, &modifiers, handle_ctrl),
+        }
+    }
+}
+
+/* German QWERTZ, the keys which differ from the US layout; '^', '´' and '`' are dead keys, see 'compose' */
+pub struct De105Key;
+
+impl KeyboardLayout for De105Key {
+    fn map_keycode(keycode: KeyCode, modifiers: &Modifiers, handle_ctrl: HandleControl) -> DecodedKey {
+        let shifted = modifiers.is_shifted();
+        let pick = |plain: char, shift: char| DecodedKey::Unicode(if shifted { shift } else { plain });
+        let letter = |lower: char, upper: char| DecodedKey::Unicode(if modifiers.is_caps() { upper } else { lower });
+        if modifiers.alt_gr {
+            match keycode {
+                KeyCode::Q => return DecodedKey::Unicode('@'),
+                KeyCode::Key7 => return DecodedKey::Unicode('{'),
+                KeyCode::Key8 => return DecodedKey::Unicode('['),
+                KeyCode::Key9 => return DecodedKey::Unicode(']'),
+                KeyCode::Key0 => return DecodedKey::Unicode('}'),
+                KeyCode::Minus => return DecodedKey::Unicode('\\'),
+                KeyCode::BracketSquareRight => return DecodedKey::Unicode('~'),
+                _ => {}
+            }
+        }
+        match keycode {
+            KeyCode::BackTick => pick('^', '°'),
+            KeyCode::Key2 if shifted => DecodedKey::Unicode('"'),
+            KeyCode::Key3 if shifted => DecodedKey::Unicode('§'),
+            KeyCode::Key6 if shifted => DecodedKey::Unicode('&'),
+            KeyCode::Key7 if shifted => DecodedKey::Unicode('/'),
+            KeyCode::Key8 if shifted => DecodedKey::Unicode('('),
+            KeyCode::Key9 if shifted => DecodedKey::Unicode(')'),
+            KeyCode::Key0 if shifted => DecodedKey::Unicode('='),
+            KeyCode::Minus => pick('ß', '?'),
+            KeyCode::Equals => pick('´', '`'),
+            KeyCode::Y => layouts::Us104Key::map_keycode(KeyCode::Z, modifiers, handle_ctrl),  // Ctrl+Z stays on the Z
+            KeyCode::Z => layouts::Us104Key::map_keycode(KeyCode::Y, modifiers, handle_ctrl),
+            KeyCode::BracketSquareLeft => letter('ü', 'Ü'),
+            KeyCode::BracketSquareRight => pick('+', '*'),
+            KeyCode::SemiColon => letter('ö', 'Ö'),
+            KeyCode::Quote => letter('ä', 'Ä'),
+            KeyCode::BackSlash => pick('#', '\''),
+            KeyCode::Comma => pick(',', ';'),
+            KeyCode::Fullstop => pick('.', ':'),
+            KeyCode::Slash => pick('-', '_'),
+            _ => layouts::Us104Key::map_keycode(keycode, modifiers, handle_ctrl),
+        }
+    }
+}
+
+/* a dead key and the key after it; a space gives the dead key itself */
+pub fn compose(dead: char, c: char) -> Option<char> {
+    let composed = match (dead, c) {
+        (_, ' ') => dead,
+        ('´', 'a') => 'á', ('´', 'e') => 'é', ('´', 'i') => 'í', ('´', 'o') => 'ó', ('´', 'u') => 'ú', ('´', 'E') => 'É',
+        ('`', 'a') => 'à', ('`', 'e') => 'è', ('`', 'i') => 'ì', ('`', 'o') => 'ò', ('`', 'u') => 'ù',
+        ('^', 'a') => 'â', ('^', 'e') => 'ê', ('^', 'i') => 'î', ('^', 'o') => 'ô', ('^', 'u') => 'û',
+        _ => return None,
+    };
+    Some(composed)
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct KeyPress {               // a decoded key and the modifiers held with it
+    pub key: DecodedKey,            // Ctrl+letter still comes as a control character, as before
+    pub ctrl: bool,
+    pub alt: bool,                  // the left Alt, the right one is AltGr
+    pub shift: bool,
+}
+
+/*
+Scancodes to key presses, for each reader of the keyboard: the keyboard task, the shells and 'edit'.
+pc_keyboard doesn't tell which modifiers are held, so they are tracked here.
+*/
+pub struct Decoder {
+    keyboard: Keyboard<AnyLayout, ScancodeSet1>,
+    ctrl: bool,
+    alt: bool,
+    shift: bool,
+    dead: Option<char>,             // a dead key waiting for the next one
+    locks: bool,                    // this one toggles the locks and their LEDs, see 'with_locks'
+}
+
+impl Decoder {
+    pub fn new() -> Decoder {
+        Decoder {
+            keyboard: Keyboard::new(AnyLayout, ScancodeSet1, HandleControl::MapLettersToUnicode),
+            ctrl: false,
+            alt: false,
+            shift: false,
+            dead: None,
+            locks: false,
+        }
+    }
+
+    pub fn with_locks(mut self) -> Decoder {    // for the keyboard task only, which sees every key once
+        self.locks = true;
+        self
+    }
+
+    pub fn add_byte(&mut self, scancode: u8) -> Option<KeyPress> {
+        let event = match self.keyboard.add_byte(scancode) {
+            Ok(Some(event)) => event,
+            _ => return None,
+        };
+        let down = event.state == KeyState::Down;
+        match event.code {
+            KeyCode::ControlLeft | KeyCode::ControlRight => self.ctrl = down,
+            KeyCode::AltLeft => self.alt = down,
+            KeyCode::ShiftLeft | KeyCode::ShiftRight => self.shift = down,
+            KeyCode::CapsLock | KeyCode::NumpadLock | KeyCode::ScrollLock if down && self.locks => lock_key(event.code),
+            _ => {}
+        }
+        let mut key = self.keyboard.process_keyevent(event)?;
+        if let DecodedKey::Unicode(c) = key {
+            if let Some(dead) = self.dead.take() {
+                key = DecodedKey::Unicode(compose(dead, c).unwrap_or(c));
+            } else if layout() == Layout::German && (c == '^' || c == '´' || c == '`') {
+                self.dead = Some(c);
+                return None;
+            }
+        }
+        Some(KeyPress { key, ctrl: self.ctrl, alt: self.alt, shift: self.shift })
+    }
+}
+
+/* toggle a lock and show it on the LEDs; each Keyboard toggles its own locks too, but AnyLayout maps with these */
+fn lock_key(code: KeyCode) {
+    unsafe {
+        match code {
+            KeyCode::CapsLock => CAPS_LOCK = !CAPS_LOCK,
+            KeyCode::NumpadLock => NUM_LOCK = !NUM_LOCK,
+            KeyCode::ScrollLock => SCROLL_LOCK = !SCROLL_LOCK,
+            _ => return,
+        }
+        send(&[0xed, (SCROLL_LOCK as u8) | (NUM_LOCK as u8) << 1 | (CAPS_LOCK as u8) << 2]);
+    }
+}
+
+/* the PS/2 typematic setting, 250 to 1000 ms of delay and 2 to 30 repeats a second */
+pub fn set_repeat(delay: usize, rate: usize) -> Result<(), ()> {
+    if delay < 250 || delay > 1000 || rate < 2 || rate > 30 {
+        return Err(());
+    }
+    unsafe {
+        REPEAT = (delay, rate);
+    }
+    send(&[0xf3, typematic(delay, rate)]);
+    Ok(())
+}
+
+/*
+The typematic byte: bits 5-6 the delay in steps of 250 ms, bits 0-4 the rate,
+which repeats every (8 + bits 0-2) * 2^(bits 3-4) * 4.17 ms, 0 being the fastest.
+*/
+pub fn typematic(delay: usize, rate: usize) -> u8 {
+    let delay_bits = ((delay + 125) / 250).max(1).min(4) - 1;
+    let mut best = 0;
+    let mut best_error = usize::MAX;
+    for code in 0..32 {
+        let period = (8 + (code & 7)) * (1 << (code >> 3)) * 417;     // in 10 µs
+        let repeats = (100_000 + period / 2) / period;
+        let error = if repeats > rate { repeats - rate } else { rate - repeats };
+        if error < best_error {
+            best = code;
+            best_error = error;
+        }
+    }
+    (delay_bits << 5 | best) as u8
+}
+
+fn send(bytes: &[u8]) {             // to the keyboard, its 0xfa answers are dropped by the interrupt handler
+    let mut status: Port<u8> = Port::new(0x64);
+    let mut data: Port<u8> = Port::new(0x60);
+    for byte in bytes {
+        unsafe {
+            while status.read() & 0x02 != 0 {}     // the input buffer of the controller is full
+            data.write(*byte);
+        }
+    }
+}
+
+#[test_case]
+fn test_compose_and_typematic() {
+    assert_eq!(compose('´', 'e'), Some('é'));
+    assert_eq!(compose('^', ' '), Some('^'));
+    assert_eq!(compose('`', 'x'), None);
+    assert_eq!(typematic(250, 30), 0x00);
+    assert_eq!(typematic(1000, 2), 0x7f);
+    assert_eq!(typematic(500, 11) >> 5, 1);
+}
\ No newline at end of file
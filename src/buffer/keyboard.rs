@@ -1,20 +1,22 @@
 use conquer_once::spin::OnceCell;
 use crossbeam_queue::ArrayQueue;
-use crate::{print, println};
+use crate::println;
 use core::{pin::Pin, task::{Poll, Context}};
 use futures_util::stream::{Stream, StreamExt};
 use futures_util::task::AtomicWaker;
-use pc_keyboard::{layouts, DecodedKey, KeyCode, HandleControl, Keyboard, ScancodeSet1};
-use crate::terminal::controller::TerminalController;
-use crate::terminal::SwitchState;
-
-use crate::terminal::terminal1::EDITING_1;
+use pc_keyboard::{DecodedKey, KeyCode};
+use crate::terminal::session::{STATES, SESSIONS};
+use crate::terminal::jobs;
+use super::terminal_buffer::TERMINAL_WRITERS;
+use super::status_bar;
+use super::vga_buffer;
+use super::split;
+use super::selection;
+use super::kbd::Decoder;
 
 static SCANCODE_QUEUE: OnceCell<ArrayQueue<u8>> = OnceCell::uninit();   // use a OnceCell to wrap it to initialize at compile time rather than using ArrayQeueu::new()
-pub static mut SWITCH: SwitchState = SwitchState::Terminal1;
-pub static mut IF_TAB: bool = false;
-pub static mut EDIT_CHAR: [bool; 96] = [false; 96];
-pub static mut EDIT_ENTER: bool = false;
+pub static mut SWITCH: usize = 0;                 // the session shown on the screen
+const PAGE: usize = 23;                            // the lines Shift+PageUp scrolls by, the screen without the title and a line to keep
 
 static WAKER: AtomicWaker = AtomicWaker::new();
 
@@ -65,78 +67,104 @@ pub(crate) fn add_scancode(scancode: u8) { // shouldn't be callable from main.rs
 
 pub async fn print_keypresses() {
     let mut scancodes = ScancodeStream::new();
-    let mut keyboard = Keyboard::new(layouts::Us104Key, ScancodeSet1, HandleControl::Ignore);
-
-    let mut terminal_ctl1 = TerminalController::new();
-    terminal_ctl1.init();
-    let mut terminal_ctl2 = TerminalController::new();
-    terminal_ctl2.init();
+    let mut keyboard = Decoder::new().with_locks();
 
     while let Some(scancode) = scancodes.next().await {     // a while loop which make CPU work all the time
-        if let Ok(Some(key_event)) = keyboard.add_byte(scancode) {
-            if let Some(key) = keyboard.process_keyevent(key_event) {
-                match key {
-                    DecodedKey::Unicode(character) => { // normal characters
-                        match character {
-                            '\n' => {
-                                unsafe {
-                                    if EDITING_1 {
-                                        EDIT_ENTER = true;
-                                    }
-                                }
-                                println!("");
-                            },
-                            '\t' => {
-                                unsafe {
-                                    IF_TAB = true;
-                                    if SWITCH == SwitchState::Terminal1 {
-                                        SWITCH = SwitchState::Terminal2;
-                                    } else {
-                                        SWITCH = SwitchState::Terminal1;
-                                    }
-                                }
-                                print!("{}", character);
-                                unsafe {
-                                    IF_TAB = false;
-                                }
-                            },
-                            _ => {
-                                unsafe {
-                                    if EDITING_1 == true {
-                                        let c = character as i32 - 32;
-                                        // println!("\n{}", c);
-                                        if c == -24 {                   // 'backspace' use a index of 95, which is the position of 'delete' in ascii.
-                                            EDIT_CHAR[95] = true;
-                                        } else if c>=0 && c<=94 {
-                                            EDIT_CHAR[c as usize] = true;
-                                        }
-                                    }
-                                }
-                                let ch = character as u8;
-                                if ch == 0x08 {                 // backspace
-                                    print!("{}", character);
-                                } else {                        // normal characters
-                                    print!("{}", character);
+        if let Some(press) = keyboard.add_byte(scancode) {
+            if selection::is_selecting() {      // the keys move the selection, the session doesn't see them
+                selection::handle(press.key);
+                continue;
+            }
+            match press.key {
+                DecodedKey::Unicode(character) => { // the sessions read the keys themselves
+                    unsafe {
+                        if STATES[SWITCH].scrolled > 0 {    // typing goes back to the bottom
+                            scroll_to(0);
+                        }
+                    }
+                    match character {
+                        '\u{03}' => unsafe {        // Ctrl+C, reaches a running command even though its terminal reads no keys
+                            if !STATES[SWITCH].editing {
+                                jobs::interrupt(SWITCH);
+                            }
+                        },
+                        _ => {}
+                    }
+                },
+                DecodedKey::RawKey(key) => {        // other keys on the keyboard
+                    match key {
+                        KeyCode::F1 | KeyCode::F2 | KeyCode::F3 | KeyCode::F4 | KeyCode::F5 | KeyCode::F6 => unsafe {
+                            if press.alt {          // Alt+Fn, switch to session n
+                                let target = match key {
+                                    KeyCode::F1 => 0,
+                                    KeyCode::F2 => 1,
+                                    KeyCode::F3 => 2,
+                                    KeyCode::F4 => 3,
+                                    KeyCode::F5 => 4,
+                                    _ => 5,
+                                };
+                                if target < SESSIONS {
+                                    switch_terminal(target);
                                 }
                             }
-                        }
-                    },
-                    DecodedKey::RawKey(key) => {        // other keys on the keyboard
-                        match key {
-                            KeyCode::F1 => {},    // switch terminal in right cycle?
-                            KeyCode::F2 => {},    // switch terminal in left cycle?
-                            KeyCode::Escape => {},
-                            KeyCode::ArrowUp => {
-                                print!("{}", 0x04 as char);
-                            },
-                            KeyCode::ArrowDown => {
-                                print!("{}", 0x05 as char);
-                            },
-                            _ => {}
-                        }
+                        },
+                        KeyCode::F8 => unsafe {     // select text to copy
+                            if !STATES[SWITCH].editing && !split::is_split() {
+                                selection::start();
+                            }
+                        },
+                        KeyCode::PageUp | KeyCode::PageDown => unsafe {
+                            if press.shift && !STATES[SWITCH].editing && !split::is_split() {   // Shift+PageUp/PageDown, through the scrollback
+                                let back = if key == KeyCode::PageUp {
+                                    STATES[SWITCH].scrolled + PAGE
+                                } else {
+                                    STATES[SWITCH].scrolled.saturating_sub(PAGE)
+                                };
+                                scroll_to(back);
+                            }
+                        },
+                        _ => {}
                     }
-                } // https://docs.rs/pc-keyboard/0.5.1/pc_keyboard/enum.KeyCode.html
-            }
+                }
+            } // https://docs.rs/pc-keyboard/0.5.1/pc_keyboard/enum.KeyCode.html
+        }
+    }
+}
+
+fn switch_terminal(target: usize) {
+    if split::is_split() {
+        split::focus(target);
+        status_bar::render();
+        return;
+    }
+    unsafe {
+        if SWITCH == target {
+            return;
         }
+        STATES[SWITCH].scrolled = 0;    // the screen of the target is shown at the bottom
+        SWITCH = target;
     }
-}
\ No newline at end of file
+    vga_buffer::show_screen(target);
+    status_bar::render();
+}
+
+fn scroll_to(back: usize) {             // show the session 'back' lines up in its scrollback, 0 for the screen itself
+    use x86_64::instructions::interrupts;
+
+    let shown = unsafe { STATES[SWITCH].scrolled };
+    let back = interrupts::without_interrupts(|| {
+        let writer = unsafe { TERMINAL_WRITERS[SWITCH].lock() };
+        let back = back.min(writer.scrollback());
+        if back > 0 && back != shown {
+            writer.show_scrollback(back, None);
+        }
+        unsafe {
+            STATES[SWITCH].scrolled = back;     // before any other print reaches the VGA
+        }
+        back
+    });
+    if back == 0 && shown > 0 {         // the screen went on while scrolled
+        vga_buffer::show_screen(unsafe { SWITCH });
+    }
+    status_bar::render();               // it tells how far back the view is
+}
@@ -1,3 +1,9 @@
 pub mod vga_buffer;
 pub mod terminal_buffer;
-pub mod keyboard;
\ No newline at end of file
+pub mod keyboard;
+pub mod ansi;
+pub mod cp437;
+pub mod status_bar;
+pub mod split;
+pub mod selection;
+pub mod kbd;
\ No newline at end of file
@@ -0,0 +1,163 @@
+use alloc::vec::Vec;
+use alloc::format;
+use pc_keyboard::{DecodedKey, KeyCode};
+use super::terminal_buffer::TERMINAL_WRITERS;
+use super::keyboard::SWITCH;
+use super::vga_buffer;
+use super::status_bar;
+use crate::terminal::clipboard;
+use crate::terminal::session::STATES;
+
+const ROWS: usize = 24;             // the lines shown, below the status bar
+
+#[derive(Clone, Copy)]
+struct Selection {
+    cursor: (usize, usize),         // line and column, the lines of the scrollback first, then the screen
+    anchor: Option<(usize, usize)>, // where 'Space' started the selection
+    back: usize,                    // how far the view is scrolled back, as in 'keyboard::scroll_to'
+}
+
+static mut SELECTION: Option<Selection> = None;
+
+pub fn is_selecting() -> bool {
+    unsafe { SELECTION.is_some() }
+}
+
+/*
+F8: pick text off the screen or the scrollback of the terminal shown with the keyboard.
+The arrows, PageUp/PageDown, Home and End move, 'Space' marks the start,
+'Enter' copies to the clipboard (the line of the cursor if nothing is marked) and 'Esc' leaves.
+*/
+pub fn start() {
+    use x86_64::instructions::interrupts;
+
+    let cursor = interrupts::without_interrupts(|| TERMINAL_WRITERS[unsafe { SWITCH }].lock().cursor_line());
+    unsafe {
+        STATES[SWITCH].scrolled = 0;
+        STATES[SWITCH].selecting = true;    // the session gets no keys and the VGA no output meanwhile
+        SELECTION = Some(Selection { cursor, anchor: None, back: 0 });
+    }
+    render();
+}
+
+pub fn handle(key: DecodedKey) {    // a key while selecting, from the keyboard task
+    use x86_64::instructions::interrupts;
+
+    let mut selection = match unsafe { SELECTION } {
+        Some(selection) => selection,
+        None => return,
+    };
+    let (lines, history, line) = interrupts::without_interrupts(|| {
+        let writer = TERMINAL_WRITERS[unsafe { SWITCH }].lock();
+        (writer.line_count(), writer.scrollback(), writer.line(selection.cursor.0))
+    });
+    let (row, col) = selection.cursor;
+    match key {
+        DecodedKey::RawKey(KeyCode::ArrowUp) => selection.cursor.0 = row.saturating_sub(1),
+        DecodedKey::RawKey(KeyCode::ArrowDown) => selection.cursor.0 = (row + 1).min(lines - 1),
+        DecodedKey::RawKey(KeyCode::ArrowLeft) => selection.cursor.1 = col.saturating_sub(1),
+        DecodedKey::RawKey(KeyCode::ArrowRight) => selection.cursor.1 = (col + 1).min(line.len() - 1),
+        DecodedKey::RawKey(KeyCode::PageUp) => selection.cursor.0 = row.saturating_sub(ROWS - 1),
+        DecodedKey::RawKey(KeyCode::PageDown) => selection.cursor.0 = (row + ROWS - 1).min(lines - 1),
+        DecodedKey::RawKey(KeyCode::Home) => selection.cursor.1 = 0,
+        DecodedKey::RawKey(KeyCode::End) => selection.cursor.1 = trimmed(&line).len().saturating_sub(1),
+        DecodedKey::Unicode(' ') => {
+            selection.anchor = match selection.anchor {
+                Some(_) => None,
+                None => Some(selection.cursor),
+            };
+        },
+        DecodedKey::Unicode('\n') => {
+            let (start, end) = match selection.anchor {
+                Some(anchor) if anchor <= selection.cursor => (anchor, selection.cursor),
+                Some(anchor) => (selection.cursor, anchor),
+                None => ((row, 0), (row, line.len() - 1)),
+            };
+            let lines: Vec<Vec<char>> = interrupts::without_interrupts(|| {
+                let writer = TERMINAL_WRITERS[unsafe { SWITCH }].lock();
+                (start.0..=end.0).map(|i| writer.line(i)).collect()
+            });
+            let text = selected_text(&lines, start.1, end.1);
+            let count = text.len();
+            clipboard::copy(text);
+            leave();
+            status_bar::notify(&format!("Copied {} characters", count));
+            return;
+        },
+        DecodedKey::Unicode('\u{1b}') | DecodedKey::RawKey(KeyCode::F8) => {
+            leave();
+            return;
+        },
+        _ => {}
+    }
+
+    let top = history - selection.back;             // keep the cursor in view
+    if selection.cursor.0 < top {
+        selection.back = history - selection.cursor.0;
+    } else if selection.cursor.0 >= top + ROWS {
+        selection.back = (history + ROWS - 1).saturating_sub(selection.cursor.0);
+    }
+    unsafe {
+        SELECTION = Some(selection);
+    }
+    render();
+}
+
+fn leave() {
+    unsafe {
+        SELECTION = None;
+        STATES[SWITCH].selecting = false;
+    }
+    vga_buffer::show_screen(unsafe { SWITCH });
+    status_bar::render();
+}
+
+fn render() {
+    use x86_64::instructions::interrupts;
+
+    if let Some(selection) = unsafe { SELECTION } {
+        let marked = match selection.anchor {
+            Some(anchor) if anchor <= selection.cursor => (anchor, selection.cursor),
+            Some(anchor) => (selection.cursor, anchor),
+            None => (selection.cursor, selection.cursor),
+        };
+        interrupts::without_interrupts(|| {
+            TERMINAL_WRITERS[unsafe { SWITCH }].lock().show_scrollback(selection.back, Some(marked));
+        });
+        status_bar::render();
+    }
+}
+
+fn trimmed(line: &[char]) -> &[char] {
+    let end = line.iter().rposition(|c| *c != ' ').map(|i| i + 1).unwrap_or(0);
+    &line[..end]
+}
+
+/*
+The text from column 'first' of the first line to column 'last' of the last line,
+without the blanks at the end of the lines, which are joined by '\n'.
+*/
+pub fn selected_text(lines: &[Vec<char>], first: usize, last: usize) -> Vec<char> {
+    let mut text = Vec::new();
+    for (i, line) in lines.iter().enumerate() {
+        let start = if i == 0 { first } else { 0 };
+        let end = if i == lines.len() - 1 { (last + 1).min(line.len()) } else { line.len() };
+        if i > 0 {
+            text.push('\n');
+        }
+        if start < end {
+            text.extend_from_slice(trimmed(&line[start..end]));
+        }
+    }
+    text
+}
+
+#[test_case]
+fn test_selected_text() {
+    use crate::api::str2char;
+
+    let lines = [str2char("> ls   "), str2char("a.txt  b.txt  "), str2char("> ")];
+    assert_eq!(selected_text(&lines[..1], 2, 6), str2char("ls"));
+    assert_eq!(selected_text(&lines[..2], 2, 4), str2char("ls\na.txt"));
+    assert_eq!(selected_text(&lines, 2, 0), str2char("ls\na.txt  b.txt\n>"));
+}
\ No newline at end of file
@@ -0,0 +1,122 @@
+use alloc::format;
+use alloc::vec::Vec;
+use super::vga_buffer::{self, Cell, Color, BUFFER_WIDTH, BUFFER_HEIGHT};
+use super::terminal_buffer::TERMINAL_WRITERS;
+use super::ansi::Screen;
+use super::keyboard::SWITCH;
+use crate::terminal::session::SESSIONS;
+
+const PANE_ROWS: usize = 11;        // the text rows of a pane, below its header, two panes high fill rows 1..25
+
+static mut PANES: usize = 1;        // 1 for the whole screen, else 2 or 4
+static mut SHOWN: [usize; 4] = [0, 1, 2, 3];   // the sessions in the panes
+
+pub fn is_split() -> bool {
+    unsafe { PANES > 1 }
+}
+
+/*
+Tile 2 (one above the other) or 4 (two by two) sessions on the VGA, or go back to one with 'panes' 1.
+Every session is resized to a pane: its lines wrap and scroll inside it, see 'terminal_buffer::Writer::resize'.
+The session shown goes to the first pane, the ones after it to the others.
+*/
+pub fn set(panes: usize) -> Result<(), ()> {
+    use x86_64::instructions::interrupts;
+
+    let (width, height) = match panes {
+        1 => (BUFFER_WIDTH, BUFFER_HEIGHT),
+        2 => (BUFFER_WIDTH, PANE_ROWS + 1),            // and the title row
+        4 => (BUFFER_WIDTH / 2, PANE_ROWS + 1),
+        _ => return Err(()),
+    };
+    interrupts::without_interrupts(|| {
+        for writer in TERMINAL_WRITERS.iter() {
+            writer.lock().resize(width, height);
+        }
+        unsafe {
+            PANES = panes;
+            for pane in 0..4 {
+                SHOWN[pane] = (SWITCH + pane) % SESSIONS;
+            }
+        }
+    });
+    if panes == 1 {
+        vga_buffer::show_screen(unsafe { SWITCH });     // the whole screen of the session, like Alt+Fn
+    } else {
+        redraw();
+    }
+    Ok(())
+}
+
+/*
+Alt+Fn while split: the keys go to the target session, which takes the place
+of the session in the focused pane if it isn't shown yet.
+*/
+pub fn focus(target: usize) {
+    use x86_64::instructions::interrupts;
+
+    interrupts::without_interrupts(|| {
+        unsafe {
+            TERMINAL_WRITERS[SWITCH].lock().hide_cursor();     // only the focused pane blinks
+            if pane_of(target).is_none() {
+                let pane = pane_of(SWITCH).unwrap_or(0);
+                SHOWN[pane] = target;
+            }
+            SWITCH = target;
+        }
+    });
+    redraw();
+}
+
+/* copy what a session has on its screen into its pane, called for everything printed while split */
+pub fn refresh(id: usize) {
+    if let Some(pane) = pane_of(id) {
+        let (top, left, width) = geometry(unsafe { PANES }, pane);
+        vga_buffer::show_pane(id, top + 1, left, width, PANE_ROWS);
+    }
+}
+
+fn redraw() {
+    for pane in 0..unsafe { PANES } {
+        let id = unsafe { SHOWN[pane] };
+        let (top, left, width) = geometry(unsafe { PANES }, pane);
+        let (foreground, background) = if id == unsafe { SWITCH } {
+            (Color::White, Color::Blue)
+        } else {
+            (Color::LightGray, Color::DarkGray)
+        };
+        let mut header: Vec<Cell> = format!(" Terminal {} ", id + 1).bytes()
+            .map(|byte| Cell::new(byte, foreground, background)).collect();
+        header.resize(width, Cell::new(b' ', foreground, background));
+        vga_buffer::draw_cells(top, left, &header);
+        refresh(id);
+    }
+}
+
+fn pane_of(id: usize) -> Option<usize> {
+    unsafe { (0..PANES).find(|pane| SHOWN[*pane] == id) }
+}
+
+fn geometry(panes: usize, pane: usize) -> (usize, usize, usize) {    // the header row, the first column and the width of a pane
+    let top = 1 + (PANE_ROWS + 1) * if panes == 4 { pane / 2 } else { pane };
+    if panes == 4 {
+        (top, pane % 2 * BUFFER_WIDTH / 2, BUFFER_WIDTH / 2)
+    } else {
+        (top, 0, BUFFER_WIDTH)
+    }
+}
+
+#[test_case]
+fn test_pane_geometry() {
+    assert_eq!(geometry(2, 0), (1, 0, BUFFER_WIDTH));
+    assert_eq!(geometry(2, 1), (1 + PANE_ROWS + 1, 0, BUFFER_WIDTH));
+    assert_eq!(geometry(4, 1), (1, BUFFER_WIDTH / 2, BUFFER_WIDTH / 2));
+    assert_eq!(geometry(4, 2), (1 + PANE_ROWS + 1, 0, BUFFER_WIDTH / 2));
+    for &panes in [2, 4].iter() {
+        for pane in 0..panes {
+            let (top, left, width) = geometry(panes, pane);
+            assert!(top + 1 + PANE_ROWS <= BUFFER_HEIGHT);      // the header and the rows fit below the title
+            assert!(left + width <= BUFFER_WIDTH);
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use alloc::format;
+use lazy_static::lazy_static;
+use spin::Mutex;
+use super::vga_buffer::{self, Cell, Color, BUFFER_WIDTH};
+use super::keyboard::SWITCH;
+use crate::interrupts::TIMER_COUNT;
+use crate::terminal::session::{SESSIONS, STATES};
+use crate::allocator::{self, HEAP_SIZE};
+use crate::task::executor;
+use crate::timer::rtc;
+
+const NOTICE_TICKS: u64 = 100;      // how long a notification stays, 20 ticks a second
+
+struct Notice {
+    text: String,
+    until: u64,                     // the tick it goes away
+}
+
+lazy_static! {
+    static ref NOTICE: Mutex<Option<Notice>> = Mutex::new(None);
+}
+
+/*
+Show a message on the right of the status bar for a few seconds,
+for the subsystems which have something to say whichever terminal is shown.
+*/
+pub fn notify(text: &str) {
+    let now = unsafe { TIMER_COUNT };
+    *NOTICE.lock() = Some(Notice { text: String::from(text), until: now + NOTICE_TICKS });
+    render();
+}
+
+/*
+Draw row 0 of the VGA: the terminal shown, the uptime, the wall clock, the heap in use and the tasks,
+then the notification area. The timer task calls it twice a second, the others when what it shows changes.
+*/
+pub fn render() {
+    let now = unsafe { TIMER_COUNT };
+    let status = status(unsafe { SWITCH }, now, rtc::now(), allocator::heap_used(), executor::task_count());
+
+    let scrolled = unsafe { STATES[SWITCH].scrolled };
+    let notice = if unsafe { STATES[SWITCH].selecting } {
+        Some(String::from(" select: Space marks, Enter copies, Esc "))
+    } else if scrolled > 0 {            // see 'keyboard::scroll_to'
+        Some(format!(" scrollback -{}, Shift+PgDn ", scrolled))
+    } else {
+        let mut notice = NOTICE.lock();
+        match notice.as_ref() {
+            Some(posted) if posted.until > now => Some(format!(" {} ", posted.text)),
+            Some(_) => {
+                *notice = None;
+                None
+            },
+            None => None,
+        }
+    };
+
+    vga_buffer::draw_title(&row(&status, notice));
+}
+
+fn status(terminal: usize, ticks: u64, (hour, minute, second): (u8, u8, u8), heap: usize, tasks: usize) -> String {
+    let uptime = ticks / 20;
+    format!(" Terminal {}/{} | up {}:{:02}:{:02} | {:02}:{:02}:{:02} | heap {}K/{}K | {} tasks ",
+        terminal + 1, SESSIONS,
+        uptime / 3600, uptime / 60 % 60, uptime % 60,
+        hour, minute, second,
+        heap / 1024, HEAP_SIZE / 1024,
+        tasks)
+}
+
+fn row(status: &str, notice: Option<String>) -> Vec<Cell> {    // the status on the left, the notice over its end on the right
+    let mut cells: Vec<Cell> = status.bytes().map(|byte| Cell::new(byte, Color::Black, Color::White)).collect();
+    cells.resize(BUFFER_WIDTH, Cell::new(b' ', Color::Black, Color::White));
+    if let Some(notice) = notice {
+        let room = BUFFER_WIDTH / 2;    // it may cover the end of the status
+        let start = BUFFER_WIDTH - notice.len().min(room);
+        for (i, byte) in notice.bytes().take(room).enumerate() {
+            cells[start + i] = Cell::new(byte, Color::White, Color::Red);
+        }
+    }
+    cells.truncate(BUFFER_WIDTH);
+    cells
+}
+
+#[test_case]
+fn test_status_row() {
+    let status = status(1, 20 * 3725, (9, 5, 7), 3 * 1024, 4);
+    assert_eq!(status, format!(" Terminal 2/{} | up 1:02:05 | 09:05:07 | heap 3K/{}K | 4 tasks ", SESSIONS, HEAP_SIZE / 1024));
+
+    let cells = row(&status, None);
+    assert_eq!(cells.len(), BUFFER_WIDTH);
+    assert_eq!(cells[1], Cell::new(b'T', Color::Black, Color::White));
+    assert_eq!(cells[BUFFER_WIDTH - 1], Cell::new(b' ', Color::Black, Color::White));
+
+    let cells = row(&status, Some(String::from(" done ")));
+    assert_eq!(cells[BUFFER_WIDTH - 5], Cell::new(b'd', Color::White, Color::Red));  // at the right end
+    assert_eq!(cells[BUFFER_WIDTH - 7].background, Color::White);
+
+    let long: String = core::iter::repeat('x').take(BUFFER_WIDTH).collect();
+    let cells = row(&status, Some(long));
+    assert_eq!(cells.len(), BUFFER_WIDTH);
+    assert_eq!(cells[BUFFER_WIDTH / 2 - 1].background, Color::White);  // half the row at most
+    assert_eq!(cells[BUFFER_WIDTH / 2], Cell::new(b'x', Color::White, Color::Red));
+}
\ No newline at end of file
@@ -1,6 +1,11 @@
 #[allow(dead_code)] //  disable unused variant warnings
 
-use crate::terminal::terminal1::EDITING_1;
+use crate::terminal::session::{self, SESSIONS};
+use super::vga_buffer::Cell;
+use super::ansi::{self, Parser, Screen};
+use super::cp437::{self, Utf8};
+use crate::allocator::HEAP_SIZE;
+use alloc::vec::Vec;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)] // we enable copy semantics for the type and make it printable and comparable
 #[repr(u8)] // attribute each enum variant is stored as an u8
@@ -31,6 +36,7 @@ impl ColorCode {
         ColorCode( (background as u8) << 4 | (foreground as u8) )
     }
 }
+const DEFAULT_COLOR: ColorCode = ColorCode(ansi::DEFAULT_COLOR);
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(C)]
@@ -41,6 +47,10 @@ struct ScreenChar {     // the whole 2 bytes format printing on a signle grid
 
 const BUFFER_HEIGHT: usize = 25;    // define the size of screen
 const BUFFER_WIDTH: usize = 80;
+pub const SCROLLBACK: usize = 200;  // the lines kept after they scroll off the top of a session
+
+/* the scrollback of all the sessions, about 190 KiB, has to leave the heap to the thread stacks (8 of 64 KiB) and the rest */
+const _: () = assert!(SESSIONS * SCROLLBACK * BUFFER_WIDTH * core::mem::size_of::<ScreenChar>() <= HEAP_SIZE / 5);
 
 use volatile::Volatile;
 #[repr(transparent)]
@@ -52,88 +62,271 @@ pub struct Writer { // write something on a buffer with specified color
     pub cursor_x: usize,
     pub cursor_y: usize,
     cursor_toggle: bool,
+    under_cursor: ScreenChar,       // the cell the blinking cursor covers
+    drawn_cursor: ScreenChar,       // and what it shows there
     color_code: ColorCode,
     color_title: ColorCode,
+    ansi: Parser,                   // the escape sequence being read
+    utf8: Utf8,                     // the character being read, drawn in code page 437
+    saved: (usize, usize),          // the cursor saved by 'ESC 7'
     buffer: &'static mut Buffer, // the 'static lifetime specifies that the reference is valid for the whole program run time
+    session: usize,
+    history: Vec<[ScreenChar; BUFFER_WIDTH]>,   // the scrollback, a ring of SCROLLBACK lines allocated at once
+    oldest: usize,                  // where the ring starts once it is full
+    width: usize,                   // smaller in a pane of 'split'
+    height: usize,                  // counting the title row
 }
 impl Writer {
-    pub fn write_byte (&mut self, byte: u8) {   // write an single byte
-        match byte {
-            0x01 => self.cursor_blink(),                    // for cursor
-            0x02 => {                                       // for 'clear' command
-                for i in 1..BUFFER_HEIGHT {
-                    self.clear_row(i);
+    fn new(session: usize) -> Writer {      // the screen of a session, kept while another one is shown
+        Writer {
+            cursor_x: 0,
+            cursor_y: 0,
+            cursor_toggle: true,
+            under_cursor: ScreenChar { ascii_character: 0x00, color_code: ColorCode::new(Color::Yellow, Color::Black) },
+            drawn_cursor: ScreenChar { ascii_character: 0x00, color_code: ColorCode::new(Color::Yellow, Color::Black) },
+            color_code: DEFAULT_COLOR,
+            color_title: ColorCode::new(Color::Black, Color::White),
+            ansi: Parser::new(),
+            utf8: Utf8::new(),
+            saved: (0, 1),
+            buffer: unsafe { &mut *(session::screen(session) as *mut Buffer) },
+            session,
+            history: Vec::with_capacity(SCROLLBACK),
+            oldest: 0,
+            width: BUFFER_WIDTH,
+            height: BUFFER_HEIGHT,
+        }
+    }
+
+    fn upper_shift(&mut self) {    // print when reaching the bottom, the whole terminal should shift upper
+        let mut line = [self.buffer.chars[1][0].read(); BUFFER_WIDTH];      // row 1 leaves the screen, keep it
+        for col in 0..BUFFER_WIDTH {
+            line[col] = self.buffer.chars[1][col].read();
+        }
+        if self.history.len() < SCROLLBACK {
+            self.history.push(line);
+        } else {                    // over the oldest line
+            self.history[self.oldest] = line;
+            self.oldest = (self.oldest + 1) % SCROLLBACK;
+        }
+
+        for row in 2..self.height {
+            for col in 0..self.width {
+                let character = self.buffer.chars[row][col].read();
+                self.buffer.chars[row - 1][col].write(character);
+            }
+        }
+        self.clear_row(self.height - 1);
+        self.cursor_x = 0;
+    }
+
+    fn clear_row(&mut self, row: usize) {
+        self.erase_cells(row, 0, self.width);
+    }
+
+    fn cursor_cell(&self) -> Option<(usize, usize)> {
+        if self.cursor_x == self.width {          // reach the right side
+            if self.cursor_y != self.height - 1 {
+                return Some((self.cursor_y + 1, 0));
+            } else {                                // in the right bottom corner, the cursor should not blink any more.
+                return None;
+            }
+        }
+        Some((self.cursor_y, self.cursor_x))
+    }
+
+    pub fn cursor_blink(&mut self) {
+        let (row, col) = match self.cursor_cell() {
+            Some(cell) => cell,
+            None => return,
+        };
+
+        if self.cursor_toggle {
+            let under = self.buffer.chars[row][col].read();
+            let drawn = if under.ascii_character > b' ' && under.ascii_character <= b'~' {
+                ScreenChar {                // inside the line, show the character inverted
+                    ascii_character: under.ascii_character,
+                    color_code: ColorCode::new(Color::Black, Color::Yellow),
                 }
-                self.cursor_x = 0;
-                self.cursor_y = 1;
-            },
-            0x03 => {                                       // for 'edit' command
-                unsafe {
-                    use crate::terminal::terminal1::SHIFT_OFFSET_1;
-                    let total = self.cursor_y - SHIFT_OFFSET_1 - 2;
-                    for _ in 0..total {
-                        self.upper_shift();
-                        self.cursor_y -= 1;
-                    }
+            } else {
+                ScreenChar {                // cursor state1
+                    ascii_character: b'_',
+                    color_code: ColorCode::new(Color::Yellow, Color::Black),
                 }
-            },
-            0x04 => self.line_up(),
-            0x05 => self.line_down(),
-            0x08 => self.backspace(),                       // backspace keypress
-            0x09 => {                                       // for tab keypress
-                
-            },
-            b'\n' => self.new_line(),                       // newline when printing '\n'
-            byte => {
-                if self.cursor_x >= BUFFER_WIDTH {          // newline when typping at the right side
-                    self.cursor_x = 0;
-                    if self.cursor_y == BUFFER_HEIGHT - 1 { // reach the bottom of the VGA
-                        self.upper_shift();
-                    } else {
-                        self.cursor_y += 1;
+            };
+            self.under_cursor = under;
+            self.drawn_cursor = drawn;
+            self.buffer.chars[row][col].write(drawn);
+            self.cursor_toggle = false;
+        } else {
+            self.hide_cursor();             // cursor state2
+        }
+    }
+
+    pub fn draw_row(&mut self, row: usize, cells: &[Cell]) {    // put the cells from the left and blank the rest
+        self.hide_cursor();
+        for col in 0..BUFFER_WIDTH {
+            let character = match cells.get(col) {
+                Some(cell) => ScreenChar {
+                    ascii_character: cell.character,
+                    color_code: ColorCode((cell.background as u8) << 4 | (cell.foreground as u8)),
+                },
+                None => ScreenChar {
+                    ascii_character: 0x00,
+                    color_code: self.color_code,
+                },
+            };
+            self.buffer.chars[row][col].write(character);
+        }
+    }
+
+    pub fn move_cursor(&mut self, row: usize, col: usize) {
+        self.hide_cursor();
+        self.cursor_y = row;
+        self.cursor_x = col;
+    }
+
+    /*
+    Fit the screen into a pane of 'split', 'height' counting the title row.
+    The lines which don't fit above the cursor any more go to the scrollback, the cells outside are blanked.
+    */
+    pub fn resize(&mut self, width: usize, height: usize) {
+        self.hide_cursor();
+        let cursor_x = self.cursor_x;
+        self.width = BUFFER_WIDTH;          // shift the whole screen
+        self.height = BUFFER_HEIGHT;
+        while self.cursor_y >= height {
+            self.upper_shift();
+            self.cursor_y -= 1;
+        }
+        for row in 1..BUFFER_HEIGHT {
+            if row < height {
+                self.erase_cells(row, width, BUFFER_WIDTH);
+            } else {
+                self.clear_row(row);
+            }
+        }
+        self.width = width;
+        self.height = height;
+        self.cursor_x = cursor_x.min(width);
+        self.saved = (self.saved.0.min(width - 1), self.saved.1.min(height - 1));
+    }
+
+    pub fn scrollback(&self) -> usize {     // how many lines can be scrolled back
+        self.history.len()
+    }
+
+    /*
+    Show on the VGA the screen of the session as it was 'back' lines ago, with the scrollback above the screen.
+    The screen of the session itself keeps going, see 'vga_buffer::_print' while STATES[id].scrolled isn't 0.
+    */
+    pub fn show_scrollback(&self, back: usize, marked: Option<((usize, usize), (usize, usize))>) {  // 'marked' is shown inverted
+        let vga = unsafe { &mut *(0xb8000 as *mut Buffer) };
+        let back = back.min(self.history.len());
+        let top = self.history.len() - back;                // the first line shown, counting the screen after the scrollback
+        for row in 1..BUFFER_HEIGHT {
+            let line = top + row - 1;
+            for col in 0..BUFFER_WIDTH {
+                let mut character = self.cell(line, col);
+                if let Some((start, end)) = marked {
+                    if (line, col) >= start && (line, col) <= end {
+                        let ColorCode(color) = character.color_code;
+                        character.color_code = ColorCode(color << 4 | color >> 4);
                     }
                 }
+                vga.chars[row][col].write(character);
+            }
+        }
+    }
 
-                let row = self.cursor_y;
-                let col = self.cursor_x;
-
-                if row == 0 {                                       // print the title
-                    let color_code = self.color_title;
-                    self.buffer.chars[row][col].write(ScreenChar {
-                        ascii_character: byte,
-                        color_code,
-                    });                  
-                } else {                                            // not title
-                    let color_code = self.color_code;
-                    self.buffer.chars[row][col].write(ScreenChar {
-                        ascii_character: byte,
-                        color_code,
-                    });
-                }
+    fn cell(&self, line: usize, col: usize) -> ScreenChar {    // a line of the scrollback, or of the screen after it
+        if line < self.history.len() {
+            self.history[(self.oldest + line) % self.history.len()][col]
+        } else if line - self.history.len() + 1 < BUFFER_HEIGHT {
+            self.buffer.chars[line - self.history.len() + 1][col].read()
+        } else {
+            ScreenChar { ascii_character: 0x00, color_code: self.color_code }
+        }
+    }
+
+    pub fn line_count(&self) -> usize {     // the scrollback and the screen below the title
+        self.history.len() + self.height - 1
+    }
+
+    pub fn line(&self, line: usize) -> Vec<char> {  // the characters of a line, blanks as spaces
+        (0..self.width).map(|col| match self.cell(line, col).ascii_character {
+            0x00 => ' ',
+            byte => cp437::to_char(byte),
+        }).collect()
+    }
+
+    pub fn cursor_line(&self) -> (usize, usize) {   // where the cursor is, counted like 'line'
+        (self.history.len() + self.cursor_y.max(1) - 1, self.cursor_x.min(self.width - 1))
+    }
+}
+
+impl Screen for Writer {
+    fn size(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    fn cursor(&self) -> (usize, usize) {
+        (self.cursor_x, self.cursor_y)
+    }
+
+    fn set_cursor(&mut self, col: usize, row: usize) {
+        self.cursor_x = col;
+        self.cursor_y = row;
+    }
+
+    fn color(&self) -> u8 {
+        self.color_code.0
+    }
+
+    fn set_color(&mut self, color: u8) {
+        self.color_code = ColorCode(color);
+    }
+
+    fn saved(&mut self) -> &mut (usize, usize) {
+        &mut self.saved
+    }
 
-                self.cursor_x += 1;
+    fn decoder(&mut self) -> (&mut Parser, &mut Utf8) {
+        (&mut self.ansi, &mut self.utf8)
+    }
+
+    fn put(&mut self, glyph: u8) {
+        if self.cursor_x >= self.width {          // newline when typping at the right side
+            self.cursor_x = 0;
+            if self.cursor_y == self.height - 1 { // reach the bottom of the VGA
+                self.upper_shift();
+            } else {
+                self.cursor_y += 1;
             }
         }
+
+        let row = self.cursor_y;
+        let col = self.cursor_x;
+
+        if row == 0 {                                       // print the title
+            let color_code = self.color_title;
+            self.buffer.chars[row][col].write(ScreenChar {
+                ascii_character: glyph,
+                color_code,
+            });                  
+        } else {                                            // not title
+            let color_code = self.color_code;
+            self.buffer.chars[row][col].write(ScreenChar {
+                ascii_character: glyph,
+                color_code,
+            });
+        }
+
+        self.cursor_x += 1;
     }
 
-    fn new_line(&mut self) {        
-        // if self.cursor_x < BUFFER_WIDTH {
-        //     let color_code = ColorCode::new(Color::Yellow, Color::Black);
-        //     let row = self.cursor_y;
-        //     let col = self.cursor_x;
-        //     self.buffer.chars[row][col].write(ScreenChar {
-        //         ascii_character: b' ',
-        //         color_code,
-        //     });
-        // }
-    
-        // self.cursor_x = 0;
-        // if self.cursor_y == BUFFER_HEIGHT - 1 {     // reach the bottom of the VGA
-        //     self.upper_shift();
-        // } else {
-        //     self.cursor_y += 1;
-        // }
-        if self.cursor_x < BUFFER_WIDTH {                                   // erase the cursor
+    fn new_line(&mut self) {
+        if self.cursor_x < self.width {                                   // erase the cursor
             let color_code = ColorCode::new(Color::Yellow, Color::Black);
             let row = self.cursor_y;
             let col = self.cursor_x;
@@ -144,44 +337,22 @@ impl Writer {
         }
     
         self.cursor_x = 0;
-        if self.cursor_y == BUFFER_HEIGHT - 1 {     // reach the bottom of the VGA
+        if self.cursor_y == self.height - 1 {     // reach the bottom of the VGA
             self.upper_shift();
         } else {
-            unsafe {
-                if EDITING_1 {                        // it's possible to move things out of the screen at bottom, need to be fixed
-                    let mut row = BUFFER_HEIGHT - 2;
-                    while row >= self.cursor_y {
-                        for col in 0..BUFFER_WIDTH {
-                            let character = self.buffer.chars[row][col].read();
-                            self.buffer.chars[row + 1][col].write(character);
-                        }
-                        row -= 1;
-                    }
-                    self.clear_row(self.cursor_y + 1);
-                }
-            }            
-
             self.cursor_y += 1;
         }
     }
 
-    fn upper_shift(&mut self) {    // print when reaching the bottom, the whole terminal should shift upper
-        for row in 2..BUFFER_HEIGHT {
-            for col in 0..BUFFER_WIDTH {
-                let character = self.buffer.chars[row][col].read();
-                self.buffer.chars[row - 1][col].write(character);
-            }
+    fn erase_cells(&mut self, row: usize, from: usize, to: usize) {     // blank the cells from..to of a row
+        if row == 0 {
+            return;
         }
-        self.clear_row(BUFFER_HEIGHT - 1);
-        self.cursor_x = 0;
-    }
-
-    fn clear_row(&mut self, row: usize) {
         let blank = ScreenChar {
             ascii_character: 0x00,
             color_code: self.color_code,
         };
-        for col in 0..BUFFER_WIDTH {
+        for col in from..to {
             self.buffer.chars[row][col].write(blank);
         }
     }
@@ -193,23 +364,16 @@ impl Writer {
         };
         unsafe {
             /* avoid backspacing when reach specific position */
-            if EDITING_1 == false {                               // command line mode
-                let arrow_char = self.buffer.chars[self.cursor_y][self.cursor_x - 1].read(); // the left char is '>'
-                let arrow_char = arrow_char.ascii_character;
-                if arrow_char == b'>' {
-                    return;
-                }
-            } else {                                            // text edit mode
-                /* backspace to the last character that is not ' ', these block of code only fit the situation that the line is continuous. */
-                if self.cursor_x == 0 && self.cursor_y == 2 {           // reach the first char in edit area.
-                    return;
-                }
+            let arrow_char = self.buffer.chars[self.cursor_y][self.cursor_x - 1].read(); // the left char is '>'
+            let arrow_char = arrow_char.ascii_character;
+            if arrow_char == b'>' {
+                return;
             }
 
             /* move the cursor */
             if self.cursor_x == 0 {                         // reach the left
                 let mut flag = false;
-                for i in 0..BUFFER_WIDTH {
+                for i in 0..self.width {
                     let c = self.buffer.chars[self.cursor_y - 1][i].read();
                     let c = c.ascii_character;
                     if !(c>=b' '&& c<=b'~') {
@@ -227,7 +391,7 @@ impl Writer {
                 }
                 if flag == false {
                     self.cursor_y = self.cursor_y - 1;
-                    self.cursor_x = BUFFER_WIDTH - 1;
+                    self.cursor_x = self.width - 1;
                 }
             } else {
                 self.cursor_x = self.cursor_x - 1;
@@ -236,26 +400,26 @@ impl Writer {
             let row = self.cursor_y;
             let col = self.cursor_x;
             self.buffer.chars[row][col].write(character);
-            if col == BUFFER_WIDTH - 1 {
+            if col == self.width - 1 {
                 self.buffer.chars[row + 1][0].write(character);
             } else {
                 self.buffer.chars[row][col + 1].write(character);
             }
 
             /* if the first line below the cursor is empty, shift up all the lines below */
-            if self.cursor_y == BUFFER_HEIGHT - 1 {
+            if self.cursor_y == self.height - 1 {
                 return;
             }
             let mut flag = false;
-            for col in 0..BUFFER_WIDTH {
+            for col in 0..self.width {
                 let c = self.buffer.chars[self.cursor_y + 1][col].read();
                 if c.ascii_character != 0x00 {
                     flag = true;
                 }
             }
             if flag == false {
-                for row in self.cursor_y+1..BUFFER_HEIGHT-1 {
-                    for col in 0..BUFFER_WIDTH {
+                for row in self.cursor_y+1..self.height-1 {
+                    for col in 0..self.width {
                         let c = self.buffer.chars[row + 1][col].read();
                         self.buffer.chars[row][col].write(c);
                     }
@@ -265,113 +429,35 @@ impl Writer {
                     ascii_character: 0x00,
                     color_code: ColorCode::new(Color::Yellow, Color::Black),
                 };
-                for col in 0..BUFFER_WIDTH {
-                    self.buffer.chars[BUFFER_HEIGHT - 1][col].write(character);
+                for col in 0..self.width {
+                    self.buffer.chars[self.height - 1][col].write(character);
                 }
             }
         }
     }
 
-    fn line_up(&mut self) {
-        unsafe {
-            if !EDITING_1 {       // only apply for the file editing mode
-                return;
-            }
-        }        
-        if self.cursor_y == 2 {   // reach the fist line of the text area
-            return;
-        }
-
-        for i in 0..BUFFER_WIDTH {
-            let c = self.buffer.chars[self.cursor_y - 1][i].read();
-            let c = c.ascii_character;
-            if !(c>=b' '&& c<=b'~') {
-                let character2 = ScreenChar {
-                    ascii_character: 0x00,
-                    color_code: ColorCode::new(Color::Yellow, Color::Black),
-                };
-                self.buffer.chars[self.cursor_y][self.cursor_x].write(character2);
-
-                self.cursor_x = i;
-                self.cursor_y -= 1;
-                break;
-            }
-        }
-    }
-
-    fn line_down(&mut self) {
-        unsafe {
-            if !EDITING_1 {       // only apply for the file editing mode
-                return;
-            }
-        }        
-        if self.cursor_y == BUFFER_HEIGHT - 1 {   // reach the last line of the text area
+    fn hide_cursor(&mut self) {     // put back the cell under the cursor before it moves
+        if self.cursor_toggle {
             return;
         }
-
-        for i in 0..BUFFER_WIDTH {
-            let c = self.buffer.chars[self.cursor_y + 1][i].read();
-            let c = c.ascii_character;
-            if !(c>=b' '&& c<=b'~') {
-                let character2 = ScreenChar {
-                    ascii_character: 0x00,
-                    color_code: ColorCode::new(Color::Yellow, Color::Black),
-                };
-                self.buffer.chars[self.cursor_y][self.cursor_x].write(character2);
-
-                self.cursor_x = i;
-                self.cursor_y += 1;
-                break;
+        if let Some((row, col)) = self.cursor_cell() {
+            if self.buffer.chars[row][col].read() == self.drawn_cursor {    // not overwritten yet
+                self.buffer.chars[row][col].write(self.under_cursor);
             }
         }
+        self.cursor_toggle = true;
     }
 
-    fn cursor_blink(&mut self) {
-        let character1 = ScreenChar {   // cursor state1
-            ascii_character: b'_',
-            color_code: ColorCode::new(Color::Yellow, Color::Black),
-        };
-        let character2 = ScreenChar {   // cursor state2
-            ascii_character: 0x00,
-            color_code: ColorCode::new(Color::Yellow, Color::Black),
-        };
-        
-        let mut row = self.cursor_y;
-        let mut col = self.cursor_x;
-
-        if col == BUFFER_WIDTH {        // reach the right side
-            col = 0;
-            if row != BUFFER_HEIGHT - 1 {
-                row += 1;
-            } else {                    // in the right bottom corner, the cursor should not blink any more.
-                return;
-            }
-        }
-    
-        if self.cursor_toggle {
-            self.buffer.chars[row][col].write(character1);
-            self.cursor_toggle = false;
-        } else {
-            self.buffer.chars[row][col].write(character2);
-            self.cursor_toggle = true;
-        }
-    }
-
-    pub fn write_string(&mut self, s: &str) {   // write a whole string
-        for byte in s.bytes() {
-            match byte {
-                0x20..=0x7e | 0x02 | 0x03 | 0x08 | 0x09 | 0xff | b'\n' => self.write_byte(byte),   // printable ACII byte or newline
-                0x04 | 0x05 => self.write_byte(byte),           // arrow up and arrow down
-                0x01 => self.write_byte(0x01),                  // cursor driven by timer
-                _ => self.write_byte(0xfe),                     // not part of printable ASCII range, print as '■'
-            }
-        }
+    fn clear_scrollback(&mut self) {
+        self.history.clear();
+        self.oldest = 0;
     }
 }
+
 use core::fmt;
 impl fmt::Write for Writer {
-    fn write_str(&mut self, s:&str) -> fmt::Result {    // wrap Writer::write_string in Writer::write_str
-        self.write_string(s);
+    fn write_str(&mut self, s:&str) -> fmt::Result {    // the escape sequences and the UTF-8 are read in 'ansi::write'
+        ansi::write(self, s);
         Ok(())
     }
 }
@@ -383,24 +469,32 @@ While the static are initialized at compile time, normal variables are initializ
 Use lazy statics to define a lazily initialized static that initialization happens at runtime by using macro 'lazy_static!'
 */
 lazy_static! {
-    pub static ref TERMINAL_WRITER_1: Mutex<Writer> = Mutex::new(Writer {  
-        // Use mutable variable 'Writer' and add a mutex lock on it to make it safe from data race
-        cursor_x: 0,
-        cursor_y: 0,
-        cursor_toggle: true,
-        color_code: ColorCode::new(Color::Yellow, Color::Black),
-        color_title: ColorCode::new(Color::Black, Color::White),
-        buffer: unsafe { &mut *(0xb9000 as *mut Buffer) },
-    });
+    pub static ref TERMINAL_WRITERS: [Mutex<Writer>; SESSIONS] = [
+        Mutex::new(Writer::new(0)),
+        Mutex::new(Writer::new(1)),
+        Mutex::new(Writer::new(2)),
+        Mutex::new(Writer::new(3)),
+        Mutex::new(Writer::new(4)),
+        Mutex::new(Writer::new(5)),
+    ];
+}
+
+#[test_case]
+fn test_scrollback_limit() {
+    use alloc::format;
+    use alloc::string::String;
+    use fmt::Write;
+
+    let mut writer = Writer::new(5);            // the screen of session 5, for the test only
+    let lines = SCROLLBACK + BUFFER_HEIGHT + 10;
+    for i in 0..lines {
+        writeln!(writer, "line {}", i).unwrap();
+    }
+    assert_eq!(writer.scrollback(), SCROLLBACK);    // the oldest lines went away
+    assert_eq!(writer.line_count(), SCROLLBACK + BUFFER_HEIGHT - 1);
+
+    let text = |line: usize| String::from(writer.line(line).iter().collect::<String>().trim_end());
+    let last = writer.line_count() - 2;             // above the empty line of the cursor
+    assert_eq!(text(last), format!("line {}", lines - 1));
+    assert_eq!(text(0), format!("line {}", lines - 1 - last));     // one line after the other, no gap
 }
-lazy_static! {
-    pub static ref TERMINAL_WRITER_2: Mutex<Writer> = Mutex::new(Writer {  
-        // Use mutable variable 'Writer' and add a mutex lock on it to make it safe from data race
-        cursor_x: 0,
-        cursor_y: 0,
-        cursor_toggle: true,
-        color_code: ColorCode::new(Color::Yellow, Color::Black),
-        color_title: ColorCode::new(Color::Black, Color::White),
-        buffer: unsafe { &mut *(0xba000 as *mut Buffer) },
-    });
-}
\ No newline at end of file
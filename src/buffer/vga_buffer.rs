@@ -1,16 +1,14 @@
 #[allow(dead_code)] //  disable unused variant warnings
 
-use super::terminal_buffer::{TERMINAL_WRITER_1, TERMINAL_WRITER_2};
-use super::keyboard::{SWITCH, IF_TAB};
-use crate::terminal::SwitchState;
-use crate::terminal::terminal1::PRINTING_1;
-use crate::terminal::terminal2::PRINTING_2;
-use crate::terminal::terminal1::EDITING_1;
+use super::terminal_buffer::TERMINAL_WRITERS;
+use super::keyboard::SWITCH;
+use crate::terminal::session::{self, STATES};
+use super::ansi::{self, Parser, Screen};
+use super::cp437::Utf8;
+use super::split;
+use crate::task::thread;
 
 pub static mut INITIAL: bool = false;
-pub static mut IF_ESC: bool = false;
-pub static mut EDIT_UP: bool = false;
-pub static mut EDIT_DOWN: bool = false;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)] // we enable copy semantics for the type and make it printable and comparable
 #[repr(u8)] // attribute each enum variant is stored as an u8
@@ -41,6 +39,7 @@ impl ColorCode {
         ColorCode( (background as u8) << 4 | (foreground as u8) )
     }
 }
+const DEFAULT_COLOR: ColorCode = ColorCode(ansi::DEFAULT_COLOR);
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(C)]
@@ -49,8 +48,21 @@ pub struct ScreenChar {     // the whole 2 bytes format printing on a signle gri
     color_code: ColorCode,
 }
 
-const BUFFER_HEIGHT: usize = 25;    // define the size of screen
-const BUFFER_WIDTH: usize = 80;
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cell {                   // a character drawn at a given place by a full-screen program, see 'draw_row'
+    pub character: u8,
+    pub foreground: Color,
+    pub background: Color,
+}
+
+impl Cell {
+    pub fn new(character: u8, foreground: Color, background: Color) -> Cell {
+        Cell { character, foreground, background }
+    }
+}
+
+pub const BUFFER_HEIGHT: usize = 25;    // define the size of screen
+pub const BUFFER_WIDTH: usize = 80;
 
 use volatile::Volatile;
 #[repr(transparent)]
@@ -62,104 +74,74 @@ pub struct Writer { // write something on a buffer with specified color
     cursor_x: usize,
     cursor_y: usize,
     cursor_toggle: bool,
+    under_cursor: ScreenChar,       // the cell the blinking cursor covers
+    drawn_cursor: ScreenChar,       // and what it shows there
     color_code: ColorCode,
     color_title: ColorCode,
+    ansi: Parser,                   // the escape sequence being read
+    utf8: Utf8,                     // the character being read, drawn in code page 437
+    saved: (usize, usize),          // the cursor saved by 'ESC 7'
     pub buffer: &'static mut Buffer, // the 'static lifetime specifies that the reference is valid for the whole program run time
 }
-impl Writer {
-    pub fn write_byte (&mut self, byte: u8) {   // write an single byte
-        match byte {
-            0x01 => self.cursor_blink(),                    // for cursor
-            0x02 => {                                       // for 'clear' command
-                for i in 1..BUFFER_HEIGHT {
-                    self.clear_row(i);
-                }
-                self.cursor_x = 0;
-                self.cursor_y = 1;
-            },
-            0x03 => {                                       // for 'edit' command
-                unsafe {
-                    use crate::terminal::terminal1::SHIFT_OFFSET_1;
-                    let total = self.cursor_y - SHIFT_OFFSET_1 - 2;
-                    for _ in 0..total {
-                        self.upper_shift();
-                        self.cursor_y -= 1;
-                    }
-                }
-            },
-            0x04 => self.line_up(),
-            0x05 => self.line_down(),
-            0x08 => self.backspace(),                       // backspace keypress
-            0x09 => self.switch(),                          // for tab keypress
-            0x1b => {                                       // for esc, interrupt the command
-                unsafe {
-                    IF_ESC = true;
-                }
-            },
-            b'\n' => self.new_line(),                       // newline when printing '\n'
-            byte => {
-                if self.cursor_x >= BUFFER_WIDTH {          // newline when typping at the right side
-                    self.cursor_x = 0;
-                    
-                    if self.cursor_y == BUFFER_HEIGHT - 1 { // reach the bottom of the VGA
-                        self.upper_shift();
-                    } else {
-                        self.cursor_y += 1;
-                    }
-                }
+impl Screen for Writer {
+    fn size(&self) -> (usize, usize) {
+        (BUFFER_WIDTH, BUFFER_HEIGHT)
+    }
 
-                let row = self.cursor_y;
-                let col = self.cursor_x;
-
-                if row == 0 {                                       // print the title
-                    let color_code = self.color_title;
-                    self.buffer.chars[row][col].write(ScreenChar {
-                        ascii_character: byte,
-                        color_code,
-                    });                
-                } else {                                            // not title
-                    let color_code = self.color_code;
-                    self.buffer.chars[row][col].write(ScreenChar {
-                        ascii_character: byte,
-                        color_code,
-                    });
-                }
+    fn cursor(&self) -> (usize, usize) {
+        (self.cursor_x, self.cursor_y)
+    }
 
-                self.cursor_x += 1;
-            }
-        }
+    fn set_cursor(&mut self, col: usize, row: usize) {
+        self.cursor_x = col;
+        self.cursor_y = row;
     }
 
-    fn switch(&mut self) {
-        unsafe {
-            if SWITCH == SwitchState::Terminal1 {       // already switched to terminal1
-                let buffer1 = &mut *(0xb9000 as *mut Buffer);
-                for row in 0..BUFFER_HEIGHT {
-                    for col in 0..BUFFER_WIDTH {
-                        let character = buffer1.chars[row][col].read();
-                        self.buffer.chars[row][col].write(character);
-                    }
-                }
-                let color_code = self.color_title;
-                self.buffer.chars[0][10].write(ScreenChar {
-                    ascii_character: b'1',
-                    color_code,
-                });
-            } else {                                    // already switched to terminal2
-                let buffer2 = &mut *(0xba000 as *mut Buffer);
-                for row in 0..BUFFER_HEIGHT {
-                    for col in 0..BUFFER_WIDTH {
-                        let character = buffer2.chars[row][col].read();
-                        self.buffer.chars[row][col].write(character);
-                    }
-                }
-                let color_code = self.color_title;
-                self.buffer.chars[0][10].write(ScreenChar {
-                    ascii_character: b'2',
-                    color_code,
-                });
+    fn color(&self) -> u8 {
+        self.color_code.0
+    }
+
+    fn set_color(&mut self, color: u8) {
+        self.color_code = ColorCode(color);
+    }
+
+    fn saved(&mut self) -> &mut (usize, usize) {
+        &mut self.saved
+    }
+
+    fn decoder(&mut self) -> (&mut Parser, &mut Utf8) {
+        (&mut self.ansi, &mut self.utf8)
+    }
+
+    fn put(&mut self, glyph: u8) {
+        if self.cursor_x >= BUFFER_WIDTH {          // newline when typping at the right side
+            self.cursor_x = 0;
+            
+            if self.cursor_y == BUFFER_HEIGHT - 1 { // reach the bottom of the VGA
+                self.upper_shift();
+            } else {
+                self.cursor_y += 1;
             }
         }
+
+        let row = self.cursor_y;
+        let col = self.cursor_x;
+
+        if row == 0 {                                       // print the title
+            let color_code = self.color_title;
+            self.buffer.chars[row][col].write(ScreenChar {
+                ascii_character: glyph,
+                color_code,
+            });                
+        } else {                                            // not title
+            let color_code = self.color_code;
+            self.buffer.chars[row][col].write(ScreenChar {
+                ascii_character: glyph,
+                color_code,
+            });
+        }
+
+        self.cursor_x += 1;
     }
 
     fn new_line(&mut self) {
@@ -177,41 +159,19 @@ impl Writer {
         if self.cursor_y == BUFFER_HEIGHT - 1 {     // reach the bottom of the VGA
             self.upper_shift();
         } else {
-            unsafe {
-                if EDITING_1 {                        // it's possible to move things out of the screen at bottom, need to be fixed
-                    let mut row = BUFFER_HEIGHT - 2;
-                    while row >= self.cursor_y {
-                        for col in 0..BUFFER_WIDTH {
-                            let character = self.buffer.chars[row][col].read();
-                            self.buffer.chars[row + 1][col].write(character);
-                        }
-                        row -= 1;
-                    }
-                    self.clear_row(self.cursor_y + 1);
-                }
-            }            
-
             self.cursor_y += 1;
         }
     }
 
-    fn upper_shift(&mut self) {    // print when reaching the bottom, the whole terminal should shift upper
-        for row in 2..BUFFER_HEIGHT {
-            for col in 0..BUFFER_WIDTH {
-                let character = self.buffer.chars[row][col].read();
-                self.buffer.chars[row - 1][col].write(character);
-            }
+    fn erase_cells(&mut self, row: usize, from: usize, to: usize) {     // blank the cells from..to of a row
+        if row == 0 {
+            return;
         }
-        self.clear_row(BUFFER_HEIGHT - 1);
-        self.cursor_x = 0;
-    }
-
-    fn clear_row(&mut self, row: usize) {
         let blank = ScreenChar {
             ascii_character: 0x00,
             color_code: self.color_code,
         };
-        for col in 0..BUFFER_WIDTH {
+        for col in from..to {
             self.buffer.chars[row][col].write(blank);
         }
     }
@@ -223,17 +183,10 @@ impl Writer {
         };
         unsafe {
             /* avoid backspacing when reach specific position */
-            if EDITING_1 == false {                               // command line mode
-                let arrow_char = self.buffer.chars[self.cursor_y][self.cursor_x - 1].read(); // the left char is '>'
-                let arrow_char = arrow_char.ascii_character;
-                if arrow_char == b'>' {
-                    return;
-                }
-            } else {                                            // text edit mode
-                /* backspace to the last character that is not ' ', these block of code only fit the situation that the line is continuous. */
-                if self.cursor_x == 0 && self.cursor_y == 2 {           // reach the first char in edit area.
-                    return;
-                }
+            let arrow_char = self.buffer.chars[self.cursor_y][self.cursor_x - 1].read(); // the left char is '>'
+            let arrow_char = arrow_char.ascii_character;
+            if arrow_char == b'>' {
+                return;
             }
 
             /* move the cursor */
@@ -302,110 +255,111 @@ impl Writer {
         }
     }
 
-    fn line_up(&mut self) {
-        unsafe { EDIT_UP = true; }
-        unsafe {
-            if !EDITING_1 {       // only apply for the file editing mode
-                return;
-            }
-        }        
-        if self.cursor_y == 2 {   // reach the fist line of the text area
+    fn hide_cursor(&mut self) {     // put back the cell under the cursor before it moves
+        if self.cursor_toggle {
             return;
         }
-
-        for i in 0..BUFFER_WIDTH {
-            let c = self.buffer.chars[self.cursor_y - 1][i].read();
-            let c = c.ascii_character;
-            if !(c>=b' '&& c<=b'~') {
-                let character2 = ScreenChar {
-                    ascii_character: 0x00,
-                    color_code: ColorCode::new(Color::Yellow, Color::Black),
-                };
-                self.buffer.chars[self.cursor_y][self.cursor_x].write(character2);
-
-                self.cursor_x = i;
-                self.cursor_y -= 1;
-                break;
+        if let Some((row, col)) = self.cursor_cell() {
+            if self.buffer.chars[row][col].read() == self.drawn_cursor {    // not overwritten yet
+                self.buffer.chars[row][col].write(self.under_cursor);
             }
         }
+        self.cursor_toggle = true;
     }
+}
 
-    fn line_down(&mut self) {
-        unsafe { EDIT_DOWN = true; }
-        unsafe {
-            if !EDITING_1 {       // only apply for the file editing mode
-                return;
+impl Writer {
+    fn upper_shift(&mut self) {    // print when reaching the bottom, the whole terminal should shift upper
+        for row in 2..BUFFER_HEIGHT {
+            for col in 0..BUFFER_WIDTH {
+                let character = self.buffer.chars[row][col].read();
+                self.buffer.chars[row - 1][col].write(character);
             }
-        }        
-        if self.cursor_y == BUFFER_HEIGHT - 1 {   // reach the last line of the text area
-            return;
         }
+        self.clear_row(BUFFER_HEIGHT - 1);
+        self.cursor_x = 0;
+    }
 
-        for i in 0..BUFFER_WIDTH {
-            let c = self.buffer.chars[self.cursor_y + 1][i].read();
-            let c = c.ascii_character;
-            if !(c>=b' '&& c<=b'~') {
-                let character2 = ScreenChar {
-                    ascii_character: 0x00,
-                    color_code: ColorCode::new(Color::Yellow, Color::Black),
-                };
-                self.buffer.chars[self.cursor_y][self.cursor_x].write(character2);
+    fn clear_row(&mut self, row: usize) {
+        self.erase_cells(row, 0, BUFFER_WIDTH);
+    }
 
-                self.cursor_x = i;
-                self.cursor_y += 1;
-                break;
+    fn cursor_cell(&self) -> Option<(usize, usize)> {
+        if self.cursor_x == BUFFER_WIDTH {          // reach the right side
+            if self.cursor_y != BUFFER_HEIGHT - 1 {
+                return Some((self.cursor_y + 1, 0));
+            } else {                                // in the right bottom corner, the cursor should not blink any more.
+                return None;
             }
         }
+        Some((self.cursor_y, self.cursor_x))
     }
 
     fn cursor_blink(&mut self) {
-        let character1 = ScreenChar {   // cursor state1
-            ascii_character: b'_',
-            color_code: ColorCode::new(Color::Yellow, Color::Black),
-        };
-        let character2 = ScreenChar {   // cursor state2
-            ascii_character: 0x00,
-            color_code: ColorCode::new(Color::Yellow, Color::Black),
+        let (row, col) = match self.cursor_cell() {
+            Some(cell) => cell,
+            None => return,
         };
-        
-        let mut row = self.cursor_y;
-        let mut col = self.cursor_x;
-
-        if col == BUFFER_WIDTH {        // reach the right side
-            col = 0;
-            if row != BUFFER_HEIGHT - 1 {
-                row += 1;
-            } else {                    // in the right bottom corner, the cursor should not blink any more.
-                return;
-            }
-        }
-    
+
         if self.cursor_toggle {
-            self.buffer.chars[row][col].write(character1);
+            let under = self.buffer.chars[row][col].read();
+            let drawn = if under.ascii_character > b' ' && under.ascii_character <= b'~' {
+                ScreenChar {                // inside the line, show the character inverted
+                    ascii_character: under.ascii_character,
+                    color_code: ColorCode::new(Color::Black, Color::Yellow),
+                }
+            } else {
+                ScreenChar {                // cursor state1
+                    ascii_character: b'_',
+                    color_code: ColorCode::new(Color::Yellow, Color::Black),
+                }
+            };
+            self.under_cursor = under;
+            self.drawn_cursor = drawn;
+            self.buffer.chars[row][col].write(drawn);
             self.cursor_toggle = false;
         } else {
-            self.buffer.chars[row][col].write(character2);
-            self.cursor_toggle = true;
+            self.hide_cursor();             // cursor state2
         }
     }
 
-    pub fn write_string(&mut self, s: &str) {   // write a whole string
-        for byte in s.bytes() {
-            match byte {
-                0x20..=0x7e | 0x02 | 0x03 | 0x08 | 0x09 | 0x1b | 0xff | b'\n' => self.write_byte(byte),
-                0x04 | 0x05 => self.write_byte(byte),           // arrow up and arrow down
-                0x01 => self.write_byte(0x01),                  // cursor driven by timer
-                // 0x02 => self.write_byte(0x02),                  // for 'clear' command
-                // 0x1b => self.write_byte(5),
-                _ => self.write_byte(0xfe),                     // not part of printable ASCII range, print as '■'
-            }
+    fn draw_row(&mut self, row: usize, cells: &[Cell]) {    // put the cells from the left and blank the rest
+        self.hide_cursor();
+        for col in 0..BUFFER_WIDTH {
+            let character = match cells.get(col) {
+                Some(cell) => ScreenChar {
+                    ascii_character: cell.character,
+                    color_code: ColorCode((cell.background as u8) << 4 | (cell.foreground as u8)),
+                },
+                None => ScreenChar {
+                    ascii_character: 0x00,
+                    color_code: self.color_code,
+                },
+            };
+            self.buffer.chars[row][col].write(character);
+        }
+    }
+
+    fn draw_cells(&mut self, row: usize, left: usize, cells: &[Cell]) {  // the status bar and the headers of the panes, the cursor stays
+        for (i, cell) in cells.iter().take(BUFFER_WIDTH - left).enumerate() {
+            self.buffer.chars[row][left + i].write(ScreenChar {
+                ascii_character: cell.character,
+                color_code: ColorCode((cell.background as u8) << 4 | (cell.foreground as u8)),
+            });
         }
     }
+
+    fn move_cursor(&mut self, row: usize, col: usize) {
+        self.hide_cursor();
+        self.cursor_y = row;
+        self.cursor_x = col;
+    }
 }
+
 use core::fmt;
 impl fmt::Write for Writer {
-    fn write_str(&mut self, s:&str) -> fmt::Result {    // wrap Writer::write_string in Writer::write_str
-        self.write_string(s);
+    fn write_str(&mut self, s:&str) -> fmt::Result {    // the escape sequences and the UTF-8 are read in 'ansi::write'
+        ansi::write(self, s);
         Ok(())
     }
 }
@@ -422,8 +376,13 @@ lazy_static! {
         cursor_x: 0,
         cursor_y: 0,
         cursor_toggle: true,
-        color_code: ColorCode::new(Color::Yellow, Color::Black),
+        under_cursor: ScreenChar { ascii_character: 0x00, color_code: ColorCode::new(Color::Yellow, Color::Black) },
+        drawn_cursor: ScreenChar { ascii_character: 0x00, color_code: ColorCode::new(Color::Yellow, Color::Black) },
+        color_code: DEFAULT_COLOR,
         color_title: ColorCode::new(Color::Black, Color::White),
+        ansi: Parser::new(),
+        utf8: Utf8::new(),
+        saved: (0, 1),
         buffer: unsafe { &mut *(0xb8000 as *mut Buffer) },
     });
 }
@@ -450,56 +409,136 @@ pub fn _print(args: fmt::Arguments) {
             if INITIAL == false {                                           // initial 'Welcome to derbo OS'
                 WRITER.lock().write_fmt(args).unwrap();
 
-                TERMINAL_WRITER_1.lock().write_fmt(args).unwrap();
-                TERMINAL_WRITER_2.lock().write_fmt(args).unwrap();
-
                 let cur_x = WRITER.lock().cursor_x;
                 let cur_y = WRITER.lock().cursor_y;
-                
-                TERMINAL_WRITER_1.lock().cursor_x = cur_x;
-                TERMINAL_WRITER_1.lock().cursor_y = cur_y;
-                TERMINAL_WRITER_2.lock().cursor_x = cur_x;
-                TERMINAL_WRITER_2.lock().cursor_y = cur_y;
+
+                for writer in TERMINAL_WRITERS.iter() {
+                    let mut writer = writer.lock();
+                    writer.write_fmt(args).unwrap();
+                    writer.cursor_x = cur_x;
+                    writer.cursor_y = cur_y;
+                }
             } else {
                 // TODO: the print of background task should not show in the VGA, and just write into TERMINAL_WRITER
-
-                /* firstly, duplicate the current TERMINAL_WRITER !!!only cursor!!! to WRITER */
-            
-                if IF_TAB {                                     // tab
-                    if SWITCH == SwitchState::Terminal1 {                   // already switched to terminal1
-                        WRITER.lock().cursor_x = TERMINAL_WRITER_1.lock().cursor_x;
-                        WRITER.lock().cursor_y = TERMINAL_WRITER_1.lock().cursor_y;
-                    } else {                                                // already switched to terminal2
-                        WRITER.lock().cursor_x = TERMINAL_WRITER_2.lock().cursor_x;
-                        WRITER.lock().cursor_y = TERMINAL_WRITER_2.lock().cursor_y;
-                    }
-                    WRITER.lock().write_fmt(args).unwrap();     // duplicate all chars from terminal buffer to VGA
+                let target = match thread::session() {
+                    Some(id) => id,                         // a thread prints to the session which started it
+                    None => match STATES.iter().position(|state| state.printing) {
+                        Some(id) => id,                     // a task prints to its own session
+                        None => SWITCH,                     // the others to the session shown
+                    },
+                };
+                if target == session::SERIAL {              // the shell on COM1 has no screen
+                    crate::serial::_print_console(args);
+                    return;
                 }
-                else {
-                    /* secondly, write chars in the VGA */
-                    if SWITCH == SwitchState::Terminal1 && PRINTING_1 && PRINTING_2 {           // I won't do this manually
-
-                    } else if SWITCH == SwitchState::Terminal1 && PRINTING_1 && !PRINTING_2 {
-                        WRITER.lock().write_fmt(args).unwrap();
-                        TERMINAL_WRITER_1.lock().write_fmt(args).unwrap();
-                    } else if SWITCH == SwitchState::Terminal1 && !PRINTING_1 && PRINTING_2 {
-                        TERMINAL_WRITER_2.lock().write_fmt(args).unwrap();
-                    } else if SWITCH == SwitchState::Terminal1 && !PRINTING_1 && !PRINTING_2 {  // !!!!!
-                        WRITER.lock().write_fmt(args).unwrap();
-                        TERMINAL_WRITER_1.lock().write_fmt(args).unwrap();
-                    } else if SWITCH == SwitchState::Terminal2 && PRINTING_1 && PRINTING_2 {    // I won't do this manually
-                        
-                    } else if SWITCH == SwitchState::Terminal2 && PRINTING_1 && !PRINTING_2 {
-                        TERMINAL_WRITER_1.lock().write_fmt(args).unwrap();
-                    } else if SWITCH == SwitchState::Terminal2 && !PRINTING_1 && PRINTING_2 {
-                        WRITER.lock().write_fmt(args).unwrap();
-                        TERMINAL_WRITER_2.lock().write_fmt(args).unwrap();
-                    } else if SWITCH == SwitchState::Terminal2 && !PRINTING_1 && !PRINTING_2 {  // !!!!! 
-                        WRITER.lock().write_fmt(args).unwrap();
-                        TERMINAL_WRITER_2.lock().write_fmt(args).unwrap();
-                    }
+                if mirrored(target) {
+                    WRITER.lock().write_fmt(args).unwrap();
+                }
+                TERMINAL_WRITERS[target].lock().write_fmt(args).unwrap();
+                if split::is_split() {
+                    split::refresh(target);
                 }
             }
         }
     });
+}
+
+/* copy the screen of a session and its cursor to the VGA, for Alt+Fn and the way back from the scrollback */
+pub fn show_screen(id: usize) {
+    use x86_64::instructions::interrupts;
+
+    interrupts::without_interrupts(|| {
+        let cursor = TERMINAL_WRITERS[id].lock().cursor();
+        let screen = unsafe { &*(session::screen(id) as *const Buffer) };
+        let mut writer = WRITER.lock();
+        writer.hide_cursor();
+        for row in 1..BUFFER_HEIGHT {                   // row 0 is the status bar, the same for all
+            for col in 0..BUFFER_WIDTH {
+                let character = screen.chars[row][col].read();
+                writer.buffer.chars[row][col].write(character);
+            }
+        }
+        writer.set_cursor(cursor.0, cursor.1);
+    });
+}
+
+pub fn blink_cursor() {             // called by the timer task, in the session shown
+    use x86_64::instructions::interrupts;
+
+    interrupts::without_interrupts(|| {
+        unsafe {
+            if mirrored(SWITCH) || !INITIAL {
+                WRITER.lock().cursor_blink();
+            }
+            TERMINAL_WRITERS[SWITCH].lock().cursor_blink();
+            if split::is_split() {
+                split::refresh(SWITCH);
+            }
+        }
+    });
+}
+
+/*
+Draw a whole row of the screen of a session, and of the VGA if it is shown,
+for full-screen programs which don't print a stream of characters.
+*/
+pub fn draw_row(id: usize, row: usize, cells: &[Cell]) {
+    use x86_64::instructions::interrupts;
+
+    interrupts::without_interrupts(|| {
+        unsafe {
+            if mirrored(id) {
+                WRITER.lock().draw_row(row, cells);
+            }
+        }
+        TERMINAL_WRITERS[id].lock().draw_row(row, cells);
+    });
+}
+
+/*
+Whether the VGA shows what the WRITER writes for a session: not when it shows the scrollback,
+a selection or the panes of 'split', which are copied from the screens of the sessions instead.
+*/
+unsafe fn mirrored(id: usize) -> bool {
+    id == SWITCH && STATES[id].scrolled == 0 && !STATES[id].selecting && !split::is_split()
+}
+
+pub fn draw_title(cells: &[Cell]) {  // the status bar, on the VGA only, see 'status_bar::render'
+    draw_cells(0, 0, cells);
+}
+
+pub fn draw_cells(row: usize, left: usize, cells: &[Cell]) {   // on the VGA only
+    use x86_64::instructions::interrupts;
+
+    interrupts::without_interrupts(|| {
+        WRITER.lock().draw_cells(row, left, cells);
+    });
+}
+
+pub fn show_pane(id: usize, top: usize, left: usize, width: usize, rows: usize) {  // rows 1.. of a session, at 'top' on the VGA
+    use x86_64::instructions::interrupts;
+
+    interrupts::without_interrupts(|| {
+        let screen = unsafe { &*(session::screen(id) as *const Buffer) };
+        let mut writer = WRITER.lock();
+        for row in 0..rows {
+            for col in 0..width {
+                let character = screen.chars[row + 1][col].read();
+                writer.buffer.chars[top + row][left + col].write(character);
+            }
+        }
+    });
+}
+
+pub fn move_cursor(id: usize, row: usize, col: usize) {
+    use x86_64::instructions::interrupts;
+
+    interrupts::without_interrupts(|| {
+        unsafe {
+            if mirrored(id) {
+                WRITER.lock().move_cursor(row, col);
+            }
+        }
+        TERMINAL_WRITERS[id].lock().move_cursor(row, col);
+    });
 }
\ No newline at end of file
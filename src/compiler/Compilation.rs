@@ -62,7 +62,7 @@ impl Compilation {
         return Compilation::new(Some(self.clone()), syntaxTree);
     }
 
-    pub fn Evaluate(&mut self, variables: Vec<VariableSymbolDictionary>) -> (EvaluationResult, Vec<VariableSymbolDictionary>) {
+    pub fn Evaluate(&mut self, variables: Vec<VariableSymbolDictionary>, session: usize) -> (EvaluationResult, Vec<VariableSymbolDictionary>) {
         let mut diagnostics = self.SyntaxTree.Diagnostics.clone();
         for diag in self.GlobalScope().unwrap().Diagnostics {
             diagnostics.push(diag);
@@ -73,7 +73,7 @@ impl Compilation {
         }
         
         let statement = self.GetStatement();
-        let mut evaluetor = Evaluator::new(statement.clone(), variables);
+        let mut evaluetor = Evaluator::new(statement.clone(), variables, session);
         let (value, vari_ret) = evaluetor.Evaluate();
 
         return (EvaluationResult::new(diagnostics, value.unwrap()), vari_ret);
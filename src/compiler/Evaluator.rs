@@ -30,14 +30,16 @@ pub struct Evaluator {
     root: BoundBlockStatement,
     variables: Vec<VariableSymbolDictionary>,
     lastValue: ValueType,
+    session: usize,                 // the shell which ran the script
 }
 
 impl Evaluator {
-    pub fn new(node: BoundBlockStatement, variables: Vec<VariableSymbolDictionary>) -> Evaluator {
+    pub fn new(node: BoundBlockStatement, variables: Vec<VariableSymbolDictionary>, session: usize) -> Evaluator {
         Evaluator {
             root: node,
             variables: variables,
             lastValue: ValueType::Null,
+            session: session,
         }
     }
 
@@ -56,6 +58,9 @@ impl Evaluator {
         let mut index = 0;
 
         while index < self.root.Statements.len() {
+            if crate::task::thread::cancelled() {     // Ctrl+C on the thread running the script
+                break;
+            }
             let s = self.root.Statements[index].clone();
 
             match s {
@@ -447,6 +452,10 @@ impl Evaluator {
         }
     }
     
+    fn printer(&self) -> usize {    // the session of the script's thread, not the one on the screen
+        crate::task::thread::session().unwrap_or(self.session)
+    }
+
     fn EvaluateCallExpression(&mut self, node: BoundCallExpression) -> Result<ValueType, ()> {
         if node.Function == BuiltinFunctions::Input() {
             
@@ -456,8 +465,8 @@ impl Evaluator {
                 ValueType::String(m) => {
                     // use crate::println;
                     // println!("{}", m);
-                    use crate::terminal::task1;
-                    task1::add_command((String::from("println"), m));
+                    use crate::terminal::session;
+                    session::add_command(self.printer(), (String::from("println"), m));
                 }
                 _ => {}
             }
@@ -482,9 +491,9 @@ impl Evaluator {
                 ValueType::Int32(sleep_time) => {
                     // use crate::api::sleep1s;
                     // sleep1s(sleep_time as u64);
-                    use crate::terminal::task1;
+                    use crate::terminal::session;
                     let time = format!("{}", sleep_time);
-                    task1::add_command((String::from("sleep"), time));
+                    session::add_command(self.printer(), (String::from("sleep"), time));
                 }
                 _ => {}
             }
@@ -7,7 +7,7 @@ use super::Compilation::Compilation;
 use super::Symbol::VariableSymbol::VariableSymbolDictionary;
 use crate::api::code2stringvec;
 
-pub fn compile_run(text_list: Vec<Vec<char>>) {
+pub fn compile_run(text_list: Vec<Vec<char>>, session: usize) {   // 'session' gets what the script prints
     let text_list = code2stringvec(text_list);
     let mut variables: Vec<VariableSymbolDictionary> = Vec::new();
     let mut previous: Option<Compilation> = None;
@@ -24,7 +24,7 @@ pub fn compile_run(text_list: Vec<Vec<char>>) {
             },
         }
         
-        let (result, vari_ret) = compilation.Evaluate(variables.clone());
+        let (result, vari_ret) = compilation.Evaluate(variables.clone(), session);
         variables = vari_ret;
 
         if result.Diagnostics.len() == 0 {
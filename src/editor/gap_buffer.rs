@@ -0,0 +1,148 @@
+use alloc::vec::Vec;
+
+/*
+The text of a document with a gap at the cursor.
+Typing and deleting at the cursor only touch the edges of the gap,
+moving the cursor moves the characters between the old and the new place across the gap.
+*/
+pub struct GapBuffer {
+    buffer: Vec<char>,
+    gap_start: usize,               // the cursor
+    gap_end: usize,                 // the first character after the gap
+}
+
+impl GapBuffer {
+    pub fn new() -> GapBuffer {
+        GapBuffer {
+            buffer: Vec::new(),
+            gap_start: 0,
+            gap_end: 0,
+        }
+    }
+
+    pub fn from(text: &[char]) -> GapBuffer {   // the cursor is left at the start
+        let mut buffer = GapBuffer::new();
+        for c in text {
+            buffer.insert(*c);
+        }
+        buffer.move_to(0);
+        buffer
+    }
+
+    pub fn len(&self) -> usize {
+        self.buffer.len() - (self.gap_end - self.gap_start)
+    }
+
+    pub fn cursor(&self) -> usize {
+        self.gap_start
+    }
+
+    pub fn get(&self, i: usize) -> Option<char> {
+        if i < self.gap_start {
+            Some(self.buffer[i])
+        } else if i < self.len() {
+            Some(self.buffer[i + self.gap_end - self.gap_start])
+        } else {
+            None
+        }
+    }
+
+    pub fn move_to(&mut self, pos: usize) {
+        let pos = if pos > self.len() { self.len() } else { pos };
+        while self.gap_start > pos {    // carry the characters before the gap to its end
+            self.gap_start -= 1;
+            self.gap_end -= 1;
+            self.buffer[self.gap_end] = self.buffer[self.gap_start];
+        }
+        while self.gap_start < pos {
+            self.buffer[self.gap_start] = self.buffer[self.gap_end];
+            self.gap_start += 1;
+            self.gap_end += 1;
+        }
+    }
+
+    pub fn insert(&mut self, c: char) { // before the cursor, which moves on
+        if self.gap_start == self.gap_end {
+            self.grow();
+        }
+        self.buffer[self.gap_start] = c;
+        self.gap_start += 1;
+    }
+
+    pub fn delete_before(&mut self) -> Option<char> {   // backspace
+        if self.gap_start == 0 {
+            return None;
+        }
+        self.gap_start -= 1;
+        Some(self.buffer[self.gap_start])
+    }
+
+    pub fn delete_after(&mut self) -> Option<char> {    // delete
+        if self.gap_end == self.buffer.len() {
+            return None;
+        }
+        self.gap_end += 1;
+        Some(self.buffer[self.gap_end - 1])
+    }
+
+    pub fn to_vec(&self) -> Vec<char> {
+        let mut text = Vec::with_capacity(self.len());
+        text.extend_from_slice(&self.buffer[..self.gap_start]);
+        text.extend_from_slice(&self.buffer[self.gap_end..]);
+        text
+    }
+
+    pub fn find(&self, pattern: &[char], from: usize) -> Option<usize> {  // the first match starting at 'from' or later
+        if pattern.len() == 0 {
+            return None;
+        }
+        let mut start = from;
+        while start + pattern.len() <= self.len() {
+            if pattern.iter().enumerate().all(|(i, c)| self.get(start + i) == Some(*c)) {
+                return Some(start);
+            }
+            start += 1;
+        }
+        None
+    }
+
+    fn grow(&mut self) {
+        let extra = if self.buffer.len() < 32 { 32 } else { self.buffer.len() };
+        let mut buffer = Vec::with_capacity(self.buffer.len() + extra);
+        buffer.extend_from_slice(&self.buffer[..self.gap_start]);
+        buffer.resize(self.gap_start + (self.gap_end - self.gap_start) + extra, '\0');
+        let gap_end = buffer.len();
+        buffer.extend_from_slice(&self.buffer[self.gap_end..]);
+        self.buffer = buffer;
+        self.gap_end = gap_end;
+    }
+}
+
+#[test_case]
+fn test_gap_buffer_edit() {
+    use crate::api::str2char;
+
+    let mut buffer = GapBuffer::from(&str2char("hello world"));
+    buffer.move_to(5);
+    buffer.insert(',');
+    assert_eq!(buffer.delete_after(), Some(' '));
+    buffer.insert('\n');
+    buffer.move_to(0);
+    assert_eq!(buffer.delete_before(), None);
+    assert_eq!(buffer.delete_after(), Some('h'));
+    buffer.insert('H');
+    assert_eq!(buffer.to_vec(), str2char("Hello,\nworld"));
+    assert_eq!(buffer.len(), 12);
+    assert_eq!(buffer.get(7), Some('w'));
+    assert_eq!(buffer.get(12), None);
+}
+
+#[test_case]
+fn test_gap_buffer_find() {
+    use crate::api::str2char;
+
+    let buffer = GapBuffer::from(&str2char("abcabc"));
+    assert_eq!(buffer.find(&str2char("bc"), 0), Some(1));
+    assert_eq!(buffer.find(&str2char("bc"), 2), Some(4));
+    assert_eq!(buffer.find(&str2char("bc"), 5), None);
+}
\ No newline at end of file
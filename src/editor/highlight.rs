@@ -0,0 +1,91 @@
+use alloc::vec::Vec;
+use alloc::vec;
+use alloc::string::String;
+use crate::buffer::vga_buffer::Color;
+use crate::compiler::Syntax::Lexer::Lexer;
+use crate::compiler::Syntax::SyntaxKind::SyntaxKind;
+use crate::compiler::Syntax::SyntaxTree::SyntaxTree;
+use crate::compiler::Text::SourceText::SourceText;
+
+pub const TEXT: Color = Color::Yellow;  // identifiers and everything not highlighted
+
+pub struct Problem {                // a part of a line the parser complains about
+    pub start: usize,
+    pub end: usize,                 // may be 'start' for something missing there
+    pub message: String,
+}
+
+impl Problem {
+    pub fn covers(&self, col: usize) -> bool {
+        col >= self.start && (col < self.end || col == self.start)
+    }
+}
+
+/*
+The color of every character of a line of a script, from the tokens the compiler's Lexer finds.
+The compiler runs a script line by line, so the lines are lexed one by one as well.
+*/
+pub fn colors(line: &[char]) -> Vec<Color> {
+    let mut colors = vec![TEXT; line.len()];
+    if !line.iter().all(|c| c.is_ascii()) {     // the Lexer counts bytes
+        return colors;
+    }
+
+    let text: String = line.iter().collect();
+    let mut lexer = Lexer::new(SourceText::From(text));
+    loop {
+        let token = lexer.Lex();
+        if token.Kind == SyntaxKind::EndOfFileToken {
+            break;
+        }
+        let start = token.Position as usize;
+        let end = start + token.Text.as_ref().map(|text| text.len()).unwrap_or(0);
+        if end <= start {
+            break;
+        }
+        for i in start..end.min(line.len()) {
+            colors[i] = color(token.Kind);
+        }
+    }
+    colors
+}
+
+pub fn problems(line: &[char]) -> Vec<Problem> {    // what the parser reports for a line
+    if !line.iter().all(|c| c.is_ascii()) || line.iter().all(|c| c.is_ascii_whitespace()) {
+        return Vec::new();
+    }
+    let tree = SyntaxTree::Parse_from_Str(line.iter().collect());
+    tree.Diagnostics.iter().map(|diagnostic| Problem {
+        start: diagnostic.Span.Start.max(0) as usize,
+        end: diagnostic.Span.end().max(0) as usize,
+        message: diagnostic.Message.clone(),
+    }).collect()
+}
+
+fn color(kind: SyntaxKind) -> Color {
+    match kind {
+        SyntaxKind::TrueKeyword | SyntaxKind::FalseKeyword => Color::Pink,
+        SyntaxKind::ElseKeyword | SyntaxKind::ForKeyword | SyntaxKind::IfKeyword | SyntaxKind::LetKeyword
+            | SyntaxKind::ToKeyword | SyntaxKind::VarKeyword | SyntaxKind::WhileKeyword => Color::LightBlue,
+        SyntaxKind::NumberToken => Color::LightCyan,
+        SyntaxKind::StringToken => Color::LightGreen,
+        SyntaxKind::BadToken => Color::LightRed,
+        SyntaxKind::IdentifierToken | SyntaxKind::WhitespaceToken | SyntaxKind::EndOfFileToken => TEXT,
+        _ => Color::White,          // operators and punctuation
+    }
+}
+
+#[test_case]
+fn test_highlight_line() {
+    use crate::api::str2char;
+
+    let colors = colors(&str2char("var a = \"x\" + 10"));
+    assert_eq!(colors[0], Color::LightBlue);
+    assert_eq!(colors[4], TEXT);
+    assert_eq!(colors[6], Color::White);
+    assert_eq!(colors[8], Color::LightGreen);
+    assert_eq!(colors[15], Color::LightCyan);
+
+    assert_eq!(problems(&str2char("var a = 10")).len(), 0);
+    assert!(problems(&str2char("var a = (10")).len() > 0);
+}
\ No newline at end of file
@@ -0,0 +1,440 @@
+use alloc::vec::Vec;
+use alloc::vec;
+use alloc::string::String;
+use alloc::format;
+use pc_keyboard::{DecodedKey, KeyCode};
+use crate::buffer::kbd::KeyPress;
+use crate::buffer::cp437;
+use crate::buffer::vga_buffer::{self, Cell, Color, BUFFER_HEIGHT, BUFFER_WIDTH};
+use crate::terminal::clipboard;
+
+pub mod gap_buffer;
+pub mod undo;
+pub mod highlight;
+
+use gap_buffer::GapBuffer;
+use undo::{UndoStack, Change};
+
+const HEADER_ROW: usize = 1;        // row 0 keeps the title of the terminal
+const TEXT_TOP: usize = 2;
+const STATUS_ROW: usize = BUFFER_HEIGHT - 1;
+const TEXT_ROWS: usize = STATUS_ROW - TEXT_TOP;
+const GUTTER: usize = 5;            // the line numbers
+const TEXT_WIDTH: usize = BUFFER_WIDTH - GUTTER;
+const TAB_WIDTH: usize = 4;         // 'Tab' inserts spaces
+const HELP: &str = "^S save  ^Q quit  ^F find  ^Z undo  ^Y redo  ^V paste";
+
+pub enum Action {                   // what the caller has to do after a key
+    None,
+    Save,
+    Quit,
+}
+
+enum Mode {
+    Edit,
+    Find(Vec<char>),                // the text typed after 'Find:'
+}
+
+/*
+A full-screen editor for one document.
+It only knows about keys and the screen, the 'edit' command reads the document,
+feeds the keys and writes the lines back when asked to save.
+*/
+pub struct Editor {
+    name: String,
+    text: GapBuffer,
+    changes: UndoStack,
+    top: usize,                     // the first line shown
+    left: usize,                    // the first column shown
+    goal: Option<usize>,            // the column kept while moving up and down
+    mode: Mode,
+    query: Vec<char>,               // the last text searched for
+    message: String,                // shown on the status line until the next key
+    quitting: bool,                 // Ctrl+Q has been pressed once with unsaved changes
+    highlighting: bool,             // the document is a script, see 'highlight'
+}
+
+impl Editor {
+    pub fn new(name: &str, content: &Vec<Vec<char>>) -> Editor {
+        let mut text = Vec::new();
+        for (i, line) in content.iter().enumerate() {
+            if i > 0 {
+                text.push('\n');
+            }
+            text.extend_from_slice(line);
+        }
+
+        Editor {
+            name: String::from(name),
+            text: GapBuffer::from(&text),
+            changes: UndoStack::new(),
+            top: 0,
+            left: 0,
+            goal: None,
+            mode: Mode::Edit,
+            query: Vec::new(),
+            message: String::new(),
+            quitting: false,
+            highlighting: false,
+        }
+    }
+
+    pub fn with_highlighting(mut self) -> Editor {
+        self.highlighting = true;
+        self
+    }
+
+    pub fn lines(&self) -> Vec<Vec<char>> {     // the document as the file system keeps it
+        self.text.to_vec().split(|c| *c == '\n').map(|line| line.to_vec()).collect()
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        self.changes.is_dirty()
+    }
+
+    pub fn saved(&mut self) {
+        self.changes.mark_saved();
+        self.message = String::from("Saved");
+    }
+
+    pub fn tell(&mut self, message: &str) {
+        self.message = String::from(message);
+    }
+
+    pub fn handle(&mut self, press: KeyPress) -> Action {
+        let key = press.key;
+        let quitting = self.quitting;
+        self.quitting = false;
+        self.message.clear();
+
+        if let Mode::Find(_) = self.mode {
+            self.handle_find(key);
+            return Action::None;
+        }
+
+        match key {
+            DecodedKey::Unicode(character) => {
+                match character {
+                    '\u{13}' => return Action::Save,                // Ctrl+S
+                    '\u{11}' => {                                   // Ctrl+Q
+                        if self.is_dirty() && !quitting {
+                            self.quitting = true;
+                            self.message = String::from("Unsaved changes, Ctrl+Q again to quit without saving");
+                        } else {
+                            return Action::Quit;
+                        }
+                    },
+                    '\u{06}' => self.mode = Mode::Find(self.query.clone()),    // Ctrl+F
+                    '\u{1a}' => self.undo(),                        // Ctrl+Z
+                    '\u{19}' => self.redo(),                        // Ctrl+Y
+                    '\u{16}' => self.paste(),                       // Ctrl+V
+                    '\u{08}' => self.backspace(),
+                    '\u{7f}' => self.delete(),
+                    '\n' => self.insert('\n'),
+                    '\t' => {
+                        for _ in 0..TAB_WIDTH {
+                            self.insert(' ');
+                        }
+                    },
+                    c if c >= ' ' && c != '\u{7f}' => self.insert(c),
+                    _ => {}                                         // the other control keys
+                }
+            },
+            DecodedKey::RawKey(key) => {
+                match key {
+                    KeyCode::ArrowLeft => self.left(),
+                    KeyCode::ArrowRight => self.right(),
+                    KeyCode::ArrowUp => self.up(1),
+                    KeyCode::ArrowDown => self.down(1),
+                    KeyCode::PageUp => self.up(TEXT_ROWS),
+                    KeyCode::PageDown => self.down(TEXT_ROWS),
+                    KeyCode::Home if press.ctrl => self.move_to(0),         // Ctrl+Home, the start of the document
+                    KeyCode::End if press.ctrl => self.move_to(self.text.len()),
+                    KeyCode::Home => {
+                        let start = self.line_start(self.text.cursor());
+                        self.move_to(start);
+                    },
+                    KeyCode::End => {
+                        let end = self.line_end(self.text.cursor());
+                        self.move_to(end);
+                    },
+                    KeyCode::Delete => self.delete(),
+                    _ => {}
+                }
+            }
+        }
+        Action::None
+    }
+
+    fn handle_find(&mut self, key: DecodedKey) {   // typing after 'Find:', 'Enter' searches and 'Esc' gives up
+        let field = match &mut self.mode {
+            Mode::Find(field) => field,
+            Mode::Edit => return,
+        };
+        match key {
+            DecodedKey::Unicode('\n') => {
+                let query = field.clone();
+                self.mode = Mode::Edit;
+                self.find(query);
+            },
+            DecodedKey::Unicode('\u{1b}') => self.mode = Mode::Edit,
+            DecodedKey::Unicode('\u{08}') => {
+                field.pop();
+            },
+            DecodedKey::Unicode(c) if c >= ' ' && c <= '~' => field.push(c),
+            _ => {}
+        }
+    }
+
+    fn find(&mut self, query: Vec<char>) {     // the next match after the cursor, going on from the start
+        if query.len() == 0 {
+            return;
+        }
+        let from = self.text.cursor() + 1;
+        match self.text.find(&query, from).or_else(|| self.text.find(&query, 0)) {
+            Some(pos) => self.move_to(pos),
+            None => {
+                let text: String = query.iter().collect();
+                self.message = format!("Not found: {}", text);
+            }
+        }
+        self.query = query;
+    }
+
+    fn insert(&mut self, c: char) {
+        let at = self.text.cursor();
+        self.text.insert(c);
+        self.changes.record(Change::Insert { at, text: vec![c] });
+        self.goal = None;
+    }
+
+    fn paste(&mut self) {                       // the clipboard at the cursor, undone at once
+        let text = clipboard::paste();
+        if text.len() == 0 {
+            self.message = String::from("The clipboard is empty, F8 in a terminal copies");
+            return;
+        }
+        let at = self.text.cursor();
+        for c in text.iter() {
+            self.text.insert(*c);
+        }
+        self.changes.seal();
+        self.changes.record(Change::Insert { at, text });
+        self.changes.seal();
+        self.goal = None;
+    }
+
+    fn backspace(&mut self) {
+        if let Some(c) = self.text.delete_before() {
+            self.changes.record(Change::Delete { at: self.text.cursor(), text: vec![c] });
+        }
+        self.goal = None;
+    }
+
+    fn delete(&mut self) {
+        if let Some(c) = self.text.delete_after() {
+            self.changes.record(Change::Delete { at: self.text.cursor(), text: vec![c] });
+        }
+        self.goal = None;
+    }
+
+    fn undo(&mut self) {
+        match self.changes.undo() {
+            Some(Change::Insert { at, text }) => {
+                self.text.move_to(at);
+                for _ in 0..text.len() {
+                    self.text.delete_after();
+                }
+            },
+            Some(Change::Delete { at, text }) => {
+                self.text.move_to(at);
+                for c in text {
+                    self.text.insert(c);
+                }
+            },
+            None => self.message = String::from("Nothing to undo"),
+        }
+        self.goal = None;
+    }
+
+    fn redo(&mut self) {
+        match self.changes.redo() {
+            Some(Change::Insert { at, text }) => {
+                self.text.move_to(at);
+                for c in text {
+                    self.text.insert(c);
+                }
+            },
+            Some(Change::Delete { at, text }) => {
+                self.text.move_to(at);
+                for _ in 0..text.len() {
+                    self.text.delete_after();
+                }
+            },
+            None => self.message = String::from("Nothing to redo"),
+        }
+        self.goal = None;
+    }
+
+    fn move_to(&mut self, pos: usize) {
+        self.text.move_to(pos);
+        self.changes.seal();        // typing somewhere else is another change
+        self.goal = None;
+    }
+
+    fn left(&mut self) {
+        let cursor = self.text.cursor();
+        if cursor > 0 {
+            self.move_to(cursor - 1);
+        }
+    }
+
+    fn right(&mut self) {
+        let cursor = self.text.cursor();
+        if cursor < self.text.len() {
+            self.move_to(cursor + 1);
+        }
+    }
+
+    fn up(&mut self, lines: usize) {
+        let (_, col) = self.position();
+        let goal = self.goal.unwrap_or(col);
+        let mut start = self.line_start(self.text.cursor());
+        for _ in 0..lines {
+            if start == 0 {
+                break;
+            }
+            start = self.line_start(start - 1);
+        }
+        let end = self.line_end(start);
+        self.move_to(if start + goal < end { start + goal } else { end });
+        self.goal = Some(goal);
+    }
+
+    fn down(&mut self, lines: usize) {
+        let (_, col) = self.position();
+        let goal = self.goal.unwrap_or(col);
+        let mut start = self.line_start(self.text.cursor());
+        for _ in 0..lines {
+            let end = self.line_end(start);
+            if end == self.text.len() {
+                break;
+            }
+            start = end + 1;
+        }
+        let end = self.line_end(start);
+        self.move_to(if start + goal < end { start + goal } else { end });
+        self.goal = Some(goal);
+    }
+
+    fn line_start(&self, pos: usize) -> usize {
+        let mut start = pos;
+        while start > 0 && self.text.get(start - 1) != Some('\n') {
+            start -= 1;
+        }
+        start
+    }
+
+    fn line_end(&self, pos: usize) -> usize {
+        let mut end = pos;
+        while end < self.text.len() && self.text.get(end) != Some('\n') {
+            end += 1;
+        }
+        end
+    }
+
+    fn position(&self) -> (usize, usize) {     // line and column of the cursor
+        let cursor = self.text.cursor();
+        let line = (0..cursor).filter(|i| self.text.get(*i) == Some('\n')).count();
+        (line, cursor - self.line_start(cursor))
+    }
+
+    fn scroll(&mut self, line: usize, col: usize) {    // keep the cursor on the screen
+        if line < self.top {
+            self.top = line;
+        } else if line >= self.top + TEXT_ROWS {
+            self.top = line + 1 - TEXT_ROWS;
+        }
+        if col < self.left {
+            self.left = col;
+        } else if col >= self.left + TEXT_WIDTH {
+            self.left = col + 1 - TEXT_WIDTH;
+        }
+    }
+
+    pub fn render(&mut self, id: usize) {  // draw the whole editor on the screen of a session
+        let (line, col) = self.position();
+        self.scroll(line, col);
+        let lines = self.lines();
+
+        let mut header = format!(" edit: {}", self.name);
+        if self.is_dirty() {
+            header.push_str(" [modified]");
+        }
+        let position = format!("Ln {}, Col {} ", line + 1, col + 1);
+        while header.len() + position.len() < BUFFER_WIDTH {
+            header.push(' ');
+        }
+        header.push_str(&position);
+        vga_buffer::draw_row(id, HEADER_ROW, &cells(&header, Color::Black, Color::LightGray));
+
+        for row in 0..TEXT_ROWS {
+            let mut text = Vec::new();
+            match lines.get(self.top + row) {
+                Some(chars) => {
+                    let (colors, problems) = if self.highlighting {
+                        (highlight::colors(chars), highlight::problems(chars))
+                    } else {
+                        (vec![highlight::TEXT; chars.len()], Vec::new())
+                    };
+                    let gutter = if problems.len() > 0 { Color::LightRed } else { Color::DarkGray };
+                    text.extend(cells(&format!("{:>4} ", self.top + row + 1), gutter, Color::Black));
+                    for (i, c) in chars.iter().enumerate().skip(self.left).take(TEXT_WIDTH) {
+                        let byte = cp437::glyph(*c);
+                        let background = if problems.iter().any(|p| p.covers(i)) { Color::Red } else { Color::Black };
+                        text.push(Cell::new(byte, colors[i], background));
+                    }
+                    let end = chars.len();          // something missing at the end, like a ')'
+                    if problems.iter().any(|p| p.start >= end) && end >= self.left && end < self.left + TEXT_WIDTH {
+                        text.push(Cell::new(b' ', highlight::TEXT, Color::Red));
+                    }
+                },
+                None => text.extend(cells("   ~", Color::DarkGray, Color::Black)),
+            }
+            vga_buffer::draw_row(id, TEXT_TOP + row, &text);
+        }
+
+        let problem = match lines.get(line) {      // what is wrong with the line of the cursor
+            Some(chars) if self.highlighting => highlight::problems(chars).into_iter().next(),
+            _ => None,
+        };
+        let (status, color) = match &self.mode {
+            Mode::Find(field) => {
+                let field: String = field.iter().collect();
+                (format!("Find: {}", field), Color::LightGray)
+            },
+            Mode::Edit if self.message.len() > 0 => (self.message.clone(), Color::LightGray),
+            Mode::Edit => match problem {
+                Some(problem) => (problem.message, Color::LightRed),
+                None => (String::from(HELP), Color::LightGray),
+            },
+        };
+        vga_buffer::draw_row(id, STATUS_ROW, &cells(&status, color, Color::Black));
+
+        match self.mode {
+            Mode::Find(_) => vga_buffer::move_cursor(id, STATUS_ROW, status.len()),
+            Mode::Edit => vga_buffer::move_cursor(id, TEXT_TOP + line - self.top, GUTTER + col - self.left),
+        }
+    }
+
+    pub fn close(&self, id: usize) {        // leave an empty screen for the shell, like 'clear'
+        for row in HEADER_ROW..BUFFER_HEIGHT {
+            vga_buffer::draw_row(id, row, &[]);
+        }
+        vga_buffer::move_cursor(id, HEADER_ROW, 0);
+    }
+}
+
+fn cells(text: &str, foreground: Color, background: Color) -> Vec<Cell> {
+    text.bytes().map(|byte| Cell::new(byte, foreground, background)).collect()
+}
\ No newline at end of file
@@ -0,0 +1,136 @@
+use alloc::vec::Vec;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    Insert { at: usize, text: Vec<char> },
+    Delete { at: usize, text: Vec<char> },
+}
+
+struct Entry {
+    change: Change,
+    id: u64,                        // tells whether the document is back to what was saved
+}
+
+/*
+The changes made to a document, newest last.
+Characters typed or deleted one after another are merged into one change,
+so that Ctrl+Z undoes a whole run of typing; moving the cursor ends the run.
+*/
+pub struct UndoStack {
+    undo: Vec<Entry>,
+    redo: Vec<Entry>,
+    next_id: u64,
+    saved: u64,                     // the id of the newest change when the document was saved, 0 for none
+    sealed: bool,                   // the next change starts a new entry
+}
+
+impl UndoStack {
+    pub fn new() -> UndoStack {
+        UndoStack {
+            undo: Vec::new(),
+            redo: Vec::new(),
+            next_id: 1,
+            saved: 0,
+            sealed: false,
+        }
+    }
+
+    pub fn record(&mut self, change: Change) {
+        self.redo.clear();
+        let id = self.next_id;
+        self.next_id += 1;
+
+        if !self.sealed {
+            if let Some(top) = self.undo.last_mut() {
+                if merge(&mut top.change, &change) {
+                    top.id = id;
+                    return;
+                }
+            }
+        }
+        self.sealed = false;
+        self.undo.push(Entry { change, id });
+    }
+
+    pub fn seal(&mut self) {
+        self.sealed = true;
+    }
+
+    pub fn undo(&mut self) -> Option<Change> {  // the change to revert
+        let entry = self.undo.pop()?;
+        let change = entry.change.clone();
+        self.redo.push(entry);
+        self.sealed = true;
+        Some(change)
+    }
+
+    pub fn redo(&mut self) -> Option<Change> {  // the change to make again
+        let entry = self.redo.pop()?;
+        let change = entry.change.clone();
+        self.undo.push(entry);
+        self.sealed = true;
+        Some(change)
+    }
+
+    pub fn mark_saved(&mut self) {
+        self.saved = self.top();
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        self.top() != self.saved
+    }
+
+    fn top(&self) -> u64 {
+        match self.undo.last() {
+            Some(entry) => entry.id,
+            None => 0,
+        }
+    }
+}
+
+fn merge(top: &mut Change, change: &Change) -> bool {  // extend a run of typing or deleting
+    match (top, change) {
+        (Change::Insert { at, text }, Change::Insert { at: new_at, text: new_text }) => {
+            if *new_at == *at + text.len() && !text.contains(&'\n') && !new_text.contains(&'\n') {
+                text.extend_from_slice(new_text);
+                return true;
+            }
+            false
+        },
+        (Change::Delete { at, text }, Change::Delete { at: new_at, text: new_text }) => {
+            if *new_at + new_text.len() == *at {        // backspace
+                let mut merged = new_text.clone();
+                merged.extend_from_slice(text);
+                *text = merged;
+                *at = *new_at;
+                return true;
+            }
+            if *new_at == *at {                         // delete
+                text.extend_from_slice(new_text);
+                return true;
+            }
+            false
+        },
+        _ => false,
+    }
+}
+
+#[test_case]
+fn test_undo_merge_and_dirty() {
+    let mut stack = UndoStack::new();
+    assert!(!stack.is_dirty());
+    stack.record(Change::Insert { at: 0, text: alloc::vec!['a'] });
+    stack.record(Change::Insert { at: 1, text: alloc::vec!['b'] });
+    stack.mark_saved();
+    assert!(!stack.is_dirty());
+    stack.record(Change::Delete { at: 1, text: alloc::vec!['b'] });
+    stack.record(Change::Delete { at: 0, text: alloc::vec!['a'] });
+    assert!(stack.is_dirty());
+
+    assert_eq!(stack.undo(), Some(Change::Delete { at: 0, text: alloc::vec!['a', 'b'] }));
+    assert!(!stack.is_dirty());
+    assert_eq!(stack.undo(), Some(Change::Insert { at: 0, text: alloc::vec!['a', 'b'] }));
+    assert_eq!(stack.undo(), None);
+    assert!(stack.redo().is_some());
+    assert!(!stack.is_dirty());
+}
\ No newline at end of file
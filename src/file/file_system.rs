@@ -170,6 +170,51 @@ impl FileSystem {
         return Ok(());
     }
 
+    pub fn read_root_file(&self, name_input: Vec<char>) -> Result<Vec<Vec<char>>, ()> {    // read a document in root without printing
+        for file in self.root.children.iter() {
+            if char_vec_cmp(&file.file_name, &name_input) && file.file_type == FileType::Document {
+                return Ok(file.content.clone());
+            }
+        }
+        return Err(());
+    }
+
+    pub fn edit_root_file(&mut self, name_input: Vec<char>, content: Vec<Vec<char>>) -> Result<(), ()> {  // edit a document in root whatever the current path is
+        let path = core::mem::replace(&mut self.path, vec![str2char("root")]);
+        let result = self.edit_file(name_input, content);
+        self.path = path;
+        result
+    }
+
+    pub fn write_file(&mut self, name_input: Vec<char>, content: Vec<Vec<char>>, append: bool) -> Result<(), ()> {   // create or overwrite a document, for '>' and '>>'
+        match self.retrieve(name_input.clone()) {
+            Ok((mut file, _)) => {
+                if file.file_type != FileType::Document {
+                    println!("This is a folder");
+                    return Err(());
+                }
+                let mut new_content = if append { file.get_content() } else { Vec::new() };
+                new_content.extend(content);
+                self.edit_file(name_input, new_content)
+            },
+            Err(_) => {
+                let mut file = FileNode::new(name_input, FileType::Document);
+                file.edit_content(content);
+                self.add_file(file)
+            }
+        }
+    }
+
+    pub fn read_folder_file(&mut self, folder: Vec<Vec<char>>, name_input: Vec<char>) -> Result<Vec<Vec<char>>, ()> {  // read a document in another folder without printing
+        let path = core::mem::replace(&mut self.path, folder);
+        let result = match self.retrieve(name_input) {
+            Ok((mut file, _)) if file.file_type == FileType::Document => Ok(file.get_content()),
+            _ => Err(()),
+        };
+        self.path = path;
+        result
+    }
+
     pub fn index_folder(&self) -> Vec<usize> {        // path中每个文件夹在它目录中的索引
         let mut current = self.root.clone();
         let mut vec_index = vec![];
@@ -289,6 +334,24 @@ impl FileSystem {
         }
     }
 
+    pub fn names(&self) -> Vec<(Vec<char>, FileType)> {    // names of the files in current folder, for the completion
+        let mut current = self.root.clone();
+        for dic in self.path.iter() {                   // current path
+            if char_vec_cmp(&*dic, &str2char("root")) {
+                continue;
+            }
+
+            for file in current.children.clone().iter() {
+                if *dic == file.file_name {
+                    current = Box::into_inner(file.clone());
+                    break;
+                }
+            }
+        }
+
+        current.children.iter().map(|file| (file.file_name.clone(), file.file_type)).collect()
+    }
+
     pub fn into_folder(&mut self, name_input: Vec<char>) {
         match self.retrieve(name_input.clone()) {
             Ok((file, _)) => {
@@ -317,6 +380,11 @@ impl FileSystem {
         }        
     }
 
+    pub fn get_folder(&self) -> String {      // the path without the '>' of the prompt
+        let names: Vec<String> = self.path.iter().map(|p| p.iter().collect()).collect();
+        names.join("\\")
+    }
+
     pub fn get_path(&self) -> String {
         let len = self.path.len();
         let mut i = 0;
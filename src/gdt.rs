@@ -5,24 +5,18 @@ use x86_64::structures::gdt::{GlobalDescriptorTable, Descriptor, SegmentSelector
 
 pub const DOUBLE_FAULT_IST_INDEX: u16 = 0;      // set stack 0 as the default fault stack
 
+/*
+The TSS is changed on every switch to a thread, see 'set_kernel_stack',
+so it is a static mut rather than a lazy static.
+*/
+static mut TSS: TaskStateSegment = TaskStateSegment::new();
+
 struct Selectors {
     code_selector: SegmentSelector,
+    data_selector: SegmentSelector,
     tss_selector: SegmentSelector,
-}
-
-lazy_static! {
-    static ref TSS: TaskStateSegment = {        // define a TSS
-        let mut tss = TaskStateSegment::new();
-        tss.interrupt_stack_table[DOUBLE_FAULT_IST_INDEX as usize] = {
-            const STACK_SIZE: usize = 4096 * 5;     // set the top address because stacks on x86 grow downwards
-            static mut STACK: [u8; STACK_SIZE] = [0; STACK_SIZE];   // define the 0th IST entry as the double fault stack
-
-            let stack_start = VirtAddr::from_ptr(unsafe { &STACK });    // the compiler can't guarantee race freedom when mutable static are accessed
-            let stack_end = stack_start + STACK_SIZE;
-            stack_end
-        };
-        tss
-    };
+    user_data_selector: SegmentSelector,
+    user_code_selector: SegmentSelector,
 }
 
 lazy_static! {
@@ -32,19 +26,50 @@ lazy_static! {
 
         // since we changed out GDT, we need to let the old selector point to a different GDT descriptor
         let code_selector = gdt.add_entry(Descriptor::kernel_code_segment());
-        let tss_selector = gdt.add_entry(Descriptor::tss_segment(&TSS));
-        (gdt, Selectors { code_selector, tss_selector })
+        let data_selector = gdt.add_entry(Descriptor::kernel_data_segment());
+        let tss_selector = gdt.add_entry(Descriptor::tss_segment(unsafe { &TSS }));
+        // the segments of ring 3, their selectors come with a privilege level of 3
+        let user_data_selector = gdt.add_entry(Descriptor::user_data_segment());
+        let user_code_selector = gdt.add_entry(Descriptor::user_code_segment());
+        (gdt, Selectors { code_selector, data_selector, tss_selector, user_data_selector, user_code_selector })
     };
 }
 
 pub fn init() {
-    use x86_64::instructions::segmentation::set_cs;
+    use x86_64::instructions::segmentation::{set_cs, Segment, SS};
     use x86_64::instructions::tables::load_tss;
 
+    unsafe {
+        TSS.interrupt_stack_table[DOUBLE_FAULT_IST_INDEX as usize] = {
+            const STACK_SIZE: usize = 4096 * 5;     // set the top address because stacks on x86 grow downwards
+            static mut STACK: [u8; STACK_SIZE] = [0; STACK_SIZE];   // define the 0th IST entry as the double fault stack
+
+            let stack_start = VirtAddr::from_ptr(&STACK);   // the compiler can't guarantee race freedom when mutable static are accessed
+            let stack_end = stack_start + STACK_SIZE;
+            stack_end
+        };
+    }
+
     GDT.0.load();    // load the GDT
-    // reload CS and TSS registers
+    // reload CS and TSS registers, and SS which an 'iretq' back to ring 0 checks
     unsafe {    // the two functions are unsafe, the reason maybe possible to break memory safety by loading invalid selectors
         set_cs(GDT.1.code_selector);
+        SS::set_reg(GDT.1.data_selector);
         load_tss(GDT.1.tss_selector);
     }
-}
\ No newline at end of file
+}
+
+pub fn kernel_selectors() -> (SegmentSelector, SegmentSelector) {   // code and stack
+    (GDT.1.code_selector, GDT.1.data_selector)
+}
+
+pub fn user_selectors() -> (SegmentSelector, SegmentSelector) {
+    (GDT.1.user_code_selector, GDT.1.user_data_selector)
+}
+
+/* the stack the CPU switches to when an interrupt or a system call comes from ring 3 */
+pub fn set_kernel_stack(top: VirtAddr) {
+    unsafe {
+        TSS.privilege_stack_table[0] = top;
+    }
+}
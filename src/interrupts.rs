@@ -4,6 +4,10 @@ use pic8259::ChainedPics;
 use crate::println;
 use crate::gdt;
 use crate::hlt_loop;
+use crate::task::thread;
+use crate::process::{self, syscall};
+use x86_64::VirtAddr;
+use x86_64::PrivilegeLevel;
 use spin;
 use lazy_static::lazy_static;
 
@@ -20,6 +24,7 @@ pub static mut TIMER_COUNT: u64 = 0; // 2^64 * 0.05 is enough for stimulation
 pub enum InterruptIndex {
     Timer = PIC_1_OFFSET,   // the timer field
     Keyboard,               // the keyboard field, it defaults to plus one---interrupt 33(1 + offset32)
+    Serial = PIC_1_OFFSET + 4,  // COM1 on IRQ4
 }
 impl InterruptIndex {
     fn as_u8(self) -> u8 {
@@ -42,11 +47,23 @@ lazy_static! {
             idt.double_fault.set_handler_fn(double_fault_handler)   // set double fault handler
                 .set_stack_index(gdt::DOUBLE_FAULT_IST_INDEX);      // set the default stack
         }
-        idt[InterruptIndex::Timer.as_usize()]           // set timer interrupt handler
-            .set_handler_fn(timer_interrupt_handler);
+        unsafe {
+            idt[InterruptIndex::Timer.as_usize()]       // set timer interrupt handler, which switches threads, see 'thread::timer_entry'
+                .set_handler_addr(VirtAddr::new(thread::timer_entry as usize as u64));
+            idt[thread::YIELD_VECTOR]                   // 'thread::yield_now'
+                .set_handler_addr(VirtAddr::new(thread::yield_entry as usize as u64));
+            idt[syscall::VECTOR]                        // the system calls, allowed from ring 3
+                .set_handler_addr(VirtAddr::new(syscall::syscall_entry as usize as u64))
+                .set_privilege_level(PrivilegeLevel::Ring3);
+        }
         idt[InterruptIndex::Keyboard.as_usize()]        // set keyboard interrupt handler
             .set_handler_fn(keyboard_interrupt_handler);
+        idt[InterruptIndex::Serial.as_usize()]          // set serial interrupt handler
+            .set_handler_fn(serial_interrupt_handler);
         idt.page_fault.set_handler_fn(page_fault_handler);  // set page fault handler
+        idt.divide_error.set_handler_fn(divide_error_handler);
+        idt.invalid_opcode.set_handler_fn(invalid_opcode_handler);
+        idt.general_protection_fault.set_handler_fn(general_protection_fault_handler);
         
         idt
     };
@@ -71,8 +88,12 @@ extern "x86-interrupt" fn double_fault_handler( // define a double fault handler
     panic!("EXCEPTION: DOUBLE FAULT\n{:#?}", stack_frame);
 }
 
-extern "x86-interrupt" fn timer_interrupt_handler(  // define a timer interrupt handler which is already enabled by default
-    _stack_frame: InterruptStackFrame) // the book use '&mut', but there is an error
+/*
+The timer interrupt handler, which is already enabled by default. 'thread::timer_entry' calls it
+with the registers of the interrupted thread saved at 'rsp', and goes on with the thread whose stack it returns.
+*/
+#[no_mangle]
+extern "C" fn timer_interrupt_handler(rsp: u64) -> u64
 {
     // print!(".");
     use crate::timer::cursor;
@@ -84,37 +105,52 @@ extern "x86-interrupt" fn timer_interrupt_handler(  // define a timer interrupt
         }
         
         sleep::timerfifo_push(TIMER_COUNT);   // for sleep timer
+        sleep::wake_sleepers(TIMER_COUNT);
     }
 
     unsafe {
         PICS.lock().notify_end_of_interrupt(InterruptIndex::Timer.as_u8()); // send the EIO to tell the controller that the system is ready to receive the next interrupt
     }
+    thread::switch(rsp)
 }
 
 extern "x86-interrupt" fn keyboard_interrupt_handler(
     _stack_frame: InterruptStackFrame)
 {
     use x86_64::instructions::port::Port;
-    use crate::terminal::terminal1::TASKING_1;
-    use crate::terminal::terminal2::TASKING_2;
-    use crate::terminal::SwitchState;
-    use crate::buffer::keyboard::SWITCH;
 
     let mut port = Port::new(0x60);
     let scancode: u8 = unsafe { port.read() };
+    if scancode == 0xfa || scancode == 0xfe {   // the keyboard acknowledging a command from 'kbd', or asking for it again
+        unsafe {
+            PICS.lock().notify_end_of_interrupt(InterruptIndex::Keyboard.as_u8());
+        }
+        return;
+    }
 
     crate::buffer::keyboard::add_scancode(scancode);
+    crate::terminal::session::add_scancode(scancode);      // to the session shown
+
     unsafe {
-        if TASKING_1 == false && SWITCH == SwitchState::Terminal1 {
-            crate::terminal::terminal1::add_scancode(scancode);
-        }
-        if TASKING_2 == false && SWITCH == SwitchState::Terminal2 {
-            crate::terminal::terminal2::add_scancode(scancode);
+        PICS.lock().notify_end_of_interrupt(InterruptIndex::Keyboard.as_u8());     // send the EIO
+    }
+}
+
+extern "x86-interrupt" fn serial_interrupt_handler(
+    _stack_frame: InterruptStackFrame)
+{
+    use x86_64::instructions::port::Port;
+
+    let mut status: Port<u8> = Port::new(0x3FD);   // the line status of COM1
+    let mut data: Port<u8> = Port::new(0x3F8);
+    unsafe {
+        while status.read() & 0x01 != 0 {           // empty the FIFO
+            crate::serial::add_byte(data.read());
         }
     }
 
     unsafe {
-        PICS.lock().notify_end_of_interrupt(InterruptIndex::Keyboard.as_u8());     // send the EIO
+        PICS.lock().notify_end_of_interrupt(InterruptIndex::Serial.as_u8());
     }
 }
 
@@ -124,6 +160,9 @@ extern "x86-interrupt" fn page_fault_handler(
 ) {
     use x86_64::registers::control::Cr2;
 
+    if from_user(&stack_frame) {
+        process::fault("page fault");
+    }
     println!("EXCEPTION: PAGE FAULT");
     println!("Accessed Address: {:?}", Cr2::read());
     println!("Error Code: {:?}", error_code);
@@ -131,6 +170,37 @@ extern "x86-interrupt" fn page_fault_handler(
     hlt_loop();
 }
 
+fn from_user(stack_frame: &InterruptStackFrame) -> bool {  // the fault is a process's, see 'process::fault'
+    stack_frame.code_segment & 3 == 3
+}
+
+extern "x86-interrupt" fn divide_error_handler(
+    stack_frame: InterruptStackFrame)
+{
+    if from_user(&stack_frame) {
+        process::fault("divide error");
+    }
+    panic!("EXCEPTION: DIVIDE ERROR\n{:#?}", stack_frame);
+}
+
+extern "x86-interrupt" fn invalid_opcode_handler(
+    stack_frame: InterruptStackFrame)
+{
+    if from_user(&stack_frame) {
+        process::fault("invalid opcode");
+    }
+    panic!("EXCEPTION: INVALID OPCODE\n{:#?}", stack_frame);
+}
+
+extern "x86-interrupt" fn general_protection_fault_handler(
+    stack_frame: InterruptStackFrame, error_code: u64)
+{
+    if from_user(&stack_frame) {
+        process::fault("general protection fault");
+    }
+    panic!("EXCEPTION: GENERAL PROTECTION FAULT {:#x}\n{:#?}", error_code, stack_frame);
+}
+
 #[test_case]
 fn test_breakpoint_exception() {// adding a test for interrupt::init_idt(), use the command 'cargo test --lib'
     x86_64::instructions::interrupts::int3();
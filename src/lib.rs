@@ -28,7 +28,9 @@ pub mod timer;
 pub mod terminal;
 pub mod api;
 pub mod file;
+pub mod editor;
 pub mod compiler;
+pub mod process;
 
 #[cfg(test)]
 entry_point!(test_kernel_main);
@@ -73,8 +75,14 @@ fn alloc_error_handler(layout: alloc::alloc::Layout) -> ! {
 
 #[cfg(test)]
 #[no_mangle] // don't mangle the name of this function
-fn test_kernel_main(_boot_info: &'static BootInfo) -> ! {           // named `_start` by default
+fn test_kernel_main(boot_info: &'static BootInfo) -> ! {           // named `_start` by default
+    use x86_64::VirtAddr;
+
     init();     // call the interrupt::init_idt() for the test in 'cargo test --lib'
+    let phys_mem_offset = VirtAddr::new(boot_info.physical_memory_offset);
+    let mut mapper = unsafe { memory::init(phys_mem_offset) };
+    let mut frame_allocator = unsafe { memory::BootInfoFrameAllocator::init(&boot_info.memory_map) };
+    allocator::init_heap(&mut mapper, &mut frame_allocator).expect("heap initialization failed");   // the tests of the shell allocate
 
     test_main();
     
@@ -86,6 +94,7 @@ pub fn init() {     // use interrupt::init_idt() indirectly
     interrupts::init_idt();
 
     unsafe { interrupts::PICS.lock().initialize() };
+    serial::init();
     x86_64::instructions::interrupts::enable(); // execute the 'sti' instruction to enable external interrupts
 }
 
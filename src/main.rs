@@ -11,15 +11,14 @@ extern crate alloc;
 
 use bootloader::{BootInfo, entry_point};
 use core::panic::PanicInfo;
-use DerBo_OS::{print, println};
+use DerBo_OS::println;
 use DerBo_OS::task::{Task, executor::Executor};
 
 entry_point!(kernel_main);
 
 #[no_mangle] // don't mangle the name of this function
 fn kernel_main(boot_info: &'static BootInfo) -> ! { // completely Rust funtion VS extern "C" _start
-    println!("Terminal: 1/2");
-    print!("root>");
+    println!("Terminal: 1/{}", DerBo_OS::terminal::session::SESSIONS);
 
     use DerBo_OS::buffer::vga_buffer::INITIAL;
     unsafe { INITIAL = true; }
@@ -35,6 +34,8 @@ fn kernel_main(boot_info: &'static BootInfo) -> ! { // completely Rust funtion V
     let mut mapper = unsafe { memory::init(phys_mem_offset) };  // get a offset page table
     let mut frame_allocator = unsafe { BootInfoFrameAllocator::init(&boot_info.memory_map) };
     allocator::init_heap(&mut mapper, &mut frame_allocator).expect("heap initialization failed");
+    memory::keep(mapper, frame_allocator);  // for the memory of the user processes
+    DerBo_OS::task::thread::init();     // the kernel becomes thread 0, see task/thread.rs
 
     /* trigger a breakpoint exception */
     // x86_64::instructions::interrupts::int3(); 
@@ -167,10 +168,15 @@ fn kernel_main(boot_info: &'static BootInfo) -> ! { // completely Rust funtion V
 
     let mut executor = Executor::new();
     executor.spawn(Task::new(DerBo_OS::buffer::keyboard::print_keypresses()));
-    executor.spawn(Task::new(DerBo_OS::terminal::terminal1::print_keypresses()));
-    executor.spawn(Task::new(DerBo_OS::terminal::task1::run_command()));
-    executor.spawn(Task::new(DerBo_OS::terminal::terminal2::print_keypresses()));
-    executor.spawn(Task::new(DerBo_OS::terminal::task2::run_command()));
+    use DerBo_OS::terminal::session::{self, TerminalSession, SESSIONS, SERIAL};
+    session::init();
+    for id in 0..SESSIONS {
+        executor.spawn(Task::new(TerminalSession::new(id).run_shell()));
+        executor.spawn(Task::new(TerminalSession::new(id).run_tasks()));
+    }
+    executor.spawn(Task::new(DerBo_OS::serial::read_input()));
+    executor.spawn(Task::new(session::printing_to(SERIAL, TerminalSession::new(SERIAL).run_serial())));
+    executor.spawn(Task::new(TerminalSession::new(SERIAL).run_tasks()));
     executor.spawn(Task::new(DerBo_OS::timer::cursor::print_timerfifo()));
     executor.run();     // loop
 
@@ -1,7 +1,11 @@
-use x86_64::structures::paging::{PageTable, OffsetPageTable};
-use x86_64::structures::paging::{Page, PhysFrame, Mapper, Size4KiB, FrameAllocator};
+use x86_64::structures::paging::{PageTable, OffsetPageTable, PageTableFlags};
+use x86_64::structures::paging::{Page, PhysFrame, Mapper, Translate, TranslateResult, Size4KiB, FrameAllocator, FrameDeallocator};
 use x86_64::{PhysAddr, VirtAddr};
 use bootloader::bootinfo::{MemoryMap, MemoryRegionType};
+use alloc::vec::Vec;
+use conquer_once::spin::OnceCell;
+use spin::Mutex;
+use crate::task::thread;
 
 /*
 This function is unsafe because the caller must guarantee that the complete physical memory 
@@ -35,6 +39,7 @@ pub unsafe fn init(physical_memory_offset: VirtAddr) -> OffsetPageTable<'static>
 pub struct BootInfoFrameAllocator {
     memory_map: &'static MemoryMap,
     next: usize,
+    recycled: Vec<PhysFrame>,       // frames given back, handed out again first
 }
 impl BootInfoFrameAllocator {   
     // create a FrameAllocator from the passed memory map
@@ -43,9 +48,14 @@ impl BootInfoFrameAllocator {
             memory_map,     // the memory map passed by the bootloader
             next: 0,        // keep the track of number of the next frame that the allocator should return
                             // the 'next' field is 0, and will be increased for every frame allocation
+            recycled: Vec::new(),   // doesn't allocate until a frame is given back, after the heap is there
         }   // return a BootInfoFrameAllocator instance
     }
 
+    fn free(&self) -> usize {
+        self.usable_frames().count().saturating_sub(self.next) + self.recycled.len()
+    }
+
     // return an iterator over the usable frames specified in the memory map
     fn usable_frames(&self) -> impl Iterator<Item = PhysFrame> {
         let regions = self.memory_map.iter();   // convert the memory map to an iterator of MemoryRegion
@@ -64,6 +74,9 @@ impl BootInfoFrameAllocator {
 
 unsafe impl FrameAllocator<Size4KiB> for BootInfoFrameAllocator { // for the parameter in create_example_mapping()
     fn allocate_frame(&mut self) -> Option<PhysFrame> {
+        if let Some(frame) = self.recycled.pop() {
+            return Some(frame);
+        }
         let frame = self.usable_frames().nth(self.next);// get all usable frames and get the frame with index 'next'
         self.next += 1;     // for the following frame on the next call
         frame               // return the allocated frame
@@ -72,6 +85,221 @@ unsafe impl FrameAllocator<Size4KiB> for BootInfoFrameAllocator { // for the par
 
 // a FrameAllocator that returns unsable frames from the bootloader's memory map
 
+impl FrameDeallocator<Size4KiB> for BootInfoFrameAllocator {
+    unsafe fn deallocate_frame(&mut self, frame: PhysFrame) {
+        self.recycled.push(frame);
+    }
+}
+
+/*
+The page table and the frame allocator, kept after the heap is mapped
+for the memory of the user processes, see 'process'.
+*/
+struct Memory {
+    mapper: OffsetPageTable<'static>,
+    frames: BootInfoFrameAllocator,
+    kernel: PhysFrame,              // the level 4 table of 'mapper'
+    kernel_entries: usize,          // the level 4 entries used when it was kept, none may be added
+}
+
+static MEMORY: OnceCell<Mutex<Memory>> = OnceCell::uninit();
+
+pub fn keep(mut mapper: OffsetPageTable<'static>, frames: BootInfoFrameAllocator) {
+    let (kernel, _) = x86_64::registers::control::Cr3::read();
+    assert!(mapper.level_4_table()[USER_ENTRY].is_unused(), "the kernel is mapped where the user processes go");
+    let kernel_entries = used_entries(mapper.level_4_table());
+    MEMORY.try_init_once(|| Mutex::new(Memory { mapper, frames, kernel, kernel_entries }))
+        .expect("memory::keep should only be called once");
+}
+
+fn used_entries(table: &PageTable) -> usize {
+    table.iter().filter(|entry| !entry.is_unused()).count()
+}
+
+pub fn free_frames() -> usize {     // the frames left to allocate
+    match MEMORY.try_get() {
+        Ok(memory) => memory.lock().frames.free(),
+        Err(_) => 0,
+    }
+}
+
+pub const USER_START: u64 = 0x0000_0080_0000_0000;  // the level 4 entry 1, where the kernel has nothing
+pub const USER_END: u64 = 0x0000_0100_0000_0000;
+const USER_ENTRY: usize = 1;
+
+fn pages(start: VirtAddr, size: u64) -> impl Iterator<Item = Page> {
+    let first = Page::<Size4KiB>::containing_address(start);
+    let last = Page::containing_address(start + size - 1u64);
+    Page::range_inclusive(first, last)
+}
+
+impl Memory {
+    unsafe fn table(&self, frame: PhysFrame) -> &'static mut PageTable {
+        let virt = self.mapper.phys_offset() + frame.start_address().as_u64();
+        &mut *virt.as_mut_ptr::<PageTable>()
+    }
+
+    unsafe fn mapper_of(&self, level_4: PhysFrame) -> OffsetPageTable<'static> {    // for a table which may not be active
+        OffsetPageTable::new(self.table(level_4), self.mapper.phys_offset())
+    }
+}
+
+/*
+An address space: a level 4 table of its own, sharing the entries of the kernel's table,
+so the kernel is there whichever space is active. The bootloader put the kernel low, not in
+the upper half, so it is the entry 1 which is left to the space, from USER_START to USER_END.
+The kernel must not fill another entry of its table afterwards, the spaces wouldn't see it:
+'keep' checks the entry 1 is free and 'new' that the kernel still uses the same entries.
+Used with interrupts on: a thread holding the lock may be switched out, and will give it back.
+*/
+pub struct AddressSpace {
+    level_4: PhysFrame,
+}
+
+impl AddressSpace {
+    pub fn new() -> Result<AddressSpace, ()> {
+        let mut memory = MEMORY.try_get().map_err(|_| ())?.lock();
+        assert_eq!(used_entries(memory.mapper.level_4_table()), memory.kernel_entries, "the kernel filled a level 4 entry the spaces don't share");
+        let level_4 = memory.frames.allocate_frame().ok_or(())?;
+        unsafe {
+            let table = memory.table(level_4);
+            table.zero();
+            for (i, entry) in memory.mapper.level_4_table().iter().enumerate() {
+                if i != USER_ENTRY {
+                    table[i] = entry.clone();
+                }
+            }
+        }
+        Ok(AddressSpace { level_4 })
+    }
+
+    pub fn level_4(&self) -> PhysFrame {
+        self.level_4
+    }
+
+    /* map zeroed frames in the user part; a page mapped already is kept, with these flags added */
+    pub fn map(&mut self, start: VirtAddr, size: u64, flags: PageTableFlags) -> Result<(), ()> {
+        user_range(start, size)?;
+        let flags = flags | PageTableFlags::PRESENT;
+        let parents = PageTableFlags::PRESENT | PageTableFlags::WRITABLE | PageTableFlags::USER_ACCESSIBLE;  // the pages decide
+        let mut memory = MEMORY.try_get().map_err(|_| ())?.lock();
+        let mut mapper = unsafe { memory.mapper_of(self.level_4) };
+        for page in pages(start, size) {
+            if let TranslateResult::Mapped { flags: old, .. } = mapper.translate(page.start_address()) {
+                if !old.contains(flags) {   // two segments of a program sharing a page
+                    unsafe {
+                        mapper.update_flags(page, old | flags).map_err(|_| ())?.flush();
+                    }
+                }
+                continue;
+            }
+            let frame = memory.frames.allocate_frame().ok_or(())?;
+            unsafe {
+                let virt = memory.mapper.phys_offset() + frame.start_address().as_u64();
+                core::ptr::write_bytes(virt.as_mut_ptr::<u8>(), 0, 4096);
+                match mapper.map_to_with_table_flags(page, frame, flags, parents, &mut memory.frames) {
+                    Ok(flush) => flush.flush(),
+                    Err(_) => {
+                        memory.frames.deallocate_frame(frame);
+                        return Err(());
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /* unmap and give the frames back; the page tables stay until the space is dropped */
+    pub fn unmap(&mut self, start: VirtAddr, size: u64) -> Result<(), ()> {
+        user_range(start, size)?;
+        let mut memory = MEMORY.try_get().map_err(|_| ())?.lock();
+        let mut mapper = unsafe { memory.mapper_of(self.level_4) };
+        for page in pages(start, size) {
+            if let Ok((frame, flush)) = mapper.unmap(page) {
+                flush.flush();
+                unsafe {
+                    memory.frames.deallocate_frame(frame);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /* copy into the space, through the mapping of the whole physical memory, whichever space is active */
+    pub fn write(&mut self, start: VirtAddr, bytes: &[u8]) -> Result<(), ()> {
+        let memory = MEMORY.try_get().map_err(|_| ())?.lock();
+        let mapper = unsafe { memory.mapper_of(self.level_4) };
+        let mut done = 0;
+        while done < bytes.len() {
+            let address = start + done as u64;
+            let phys = mapper.translate_addr(address).ok_or(())?;
+            let n = (4096 - (address.as_u64() & 0xfff) as usize).min(bytes.len() - done);
+            unsafe {
+                let virt = memory.mapper.phys_offset() + phys.as_u64();
+                core::ptr::copy_nonoverlapping(bytes[done..].as_ptr(), virt.as_mut_ptr::<u8>(), n);
+            }
+            done += n;
+        }
+        Ok(())
+    }
+
+    pub fn translate(&self, address: VirtAddr) -> Option<PhysAddr> {
+        let memory = MEMORY.try_get().ok()?.lock();
+        let mapper = unsafe { memory.mapper_of(self.level_4) };
+        mapper.translate_addr(address)
+    }
+
+    /* make it the space of the running thread, which the scheduler loads again after each switch back */
+    pub fn switch(&self) {
+        thread::set_table(self.level_4);
+    }
+}
+
+/*
+The frames of the user part and the tables go back. No other thread may be left on the space;
+if the running one still is, e.g. a process reaped from its own thread, it goes back to the kernel's table.
+*/
+impl Drop for AddressSpace {
+    fn drop(&mut self) {
+        let mut memory = match MEMORY.try_get() {
+            Ok(memory) => memory.lock(),
+            Err(_) => return,
+        };
+        if x86_64::registers::control::Cr3::read().0 == self.level_4 {
+            thread::set_table(memory.kernel);
+        }
+        unsafe {
+            free_level(&mut memory, self.level_4.start_address(), 4, Some(USER_ENTRY));
+            memory.frames.deallocate_frame(self.level_4);
+        }
+    }
+}
+
+fn user_range(start: VirtAddr, size: u64) -> Result<(), ()> {  // the tables of the other entries are the kernel's
+    match start.as_u64().checked_add(size) {
+        Some(end) if size > 0 && start.as_u64() >= USER_START && end <= USER_END => Ok(()),
+        _ => Err(()),
+    }
+}
+
+unsafe fn free_level(memory: &mut Memory, table: PhysAddr, level: u8, only: Option<usize>) {
+    let table = memory.table(PhysFrame::containing_address(table));
+    for (i, entry) in table.iter_mut().enumerate() {
+        if entry.is_unused() || only.map_or(false, |only| only != i) {
+            continue;
+        }
+        if entry.flags().contains(PageTableFlags::HUGE_PAGE) {     // never made by 'map', and not a table to walk
+            entry.set_unused();
+            continue;
+        }
+        if level > 1 {
+            free_level(memory, entry.addr(), level - 1, None);
+        }
+        memory.frames.deallocate_frame(PhysFrame::containing_address(entry.addr()));
+        entry.set_unused();
+    }
+}
+
 
 pub fn create_example_mapping(
     sourse_addr: u64,
@@ -0,0 +1,112 @@
+use alloc::vec::Vec;
+use core::convert::TryInto;
+use crate::memory::USER_START;
+use super::{Program, Segment, STACK_BOTTOM};
+
+/*
+ELF64 executables for x86_64, the static ones only: no interpreter and no dynamic section,
+each loadable segment linked at its address, between USER_START and the stack.
+Built e.g. with 'ld -static -Ttext-segment=0x8000000000', see process/sample.
+*/
+
+const MAGIC: [u8; 4] = [0x7f, b'E', b'L', b'F'];
+const CLASS_64: u8 = 2;
+const LITTLE_ENDIAN: u8 = 1;
+const TYPE_EXEC: u16 = 2;
+const TYPE_DYN: u16 = 3;
+const MACHINE_X86_64: u16 = 0x3e;
+const HEADER_SIZE: usize = 64;
+const PROGRAM_HEADER_SIZE: usize = 56;
+
+const PT_LOAD: u32 = 1;
+const PT_DYNAMIC: u32 = 2;
+const PT_INTERP: u32 = 3;
+const PF_X: u32 = 1;
+const PF_W: u32 = 2;
+
+fn u16_at(bytes: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(bytes.get(at..at.checked_add(2)?)?.try_into().ok()?))
+}
+
+fn u32_at(bytes: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(bytes.get(at..at.checked_add(4)?)?.try_into().ok()?))
+}
+
+fn u64_at(bytes: &[u8], at: usize) -> Option<u64> {
+    Some(u64::from_le_bytes(bytes.get(at..at.checked_add(8)?)?.try_into().ok()?))
+}
+
+pub fn is_elf(bytes: &[u8]) -> bool {
+    bytes.starts_with(&MAGIC)
+}
+
+pub fn parse(bytes: &[u8]) -> Result<Program, &'static str> {
+    if !is_elf(bytes) || bytes.len() < HEADER_SIZE {
+        return Err("not an ELF file");
+    }
+    if bytes[4] != CLASS_64 || bytes[5] != LITTLE_ENDIAN || u16_at(bytes, 18) != Some(MACHINE_X86_64) {
+        return Err("not an x86_64 ELF64 file");
+    }
+    match u16_at(bytes, 16) {
+        Some(TYPE_EXEC) => {},
+        Some(TYPE_DYN) => return Err("not a static executable"),
+        _ => return Err("not an executable"),
+    }
+    let entry = u64_at(bytes, 24).ok_or("truncated header")?;
+    let table = u64_at(bytes, 32).ok_or("truncated header")? as usize;
+    let entry_size = u16_at(bytes, 54).ok_or("truncated header")? as usize;
+    let count = u16_at(bytes, 56).ok_or("truncated header")? as usize;
+    if entry_size != PROGRAM_HEADER_SIZE {
+        return Err("bad program header size");
+    }
+
+    let mut segments = Vec::new();
+    let mut entry_found = false;
+    for i in 0..count {
+        let header = table.checked_add(i * PROGRAM_HEADER_SIZE).ok_or("bad program header")?;
+        let kind = u32_at(bytes, header).ok_or("truncated program header")?;
+        if kind == PT_INTERP || kind == PT_DYNAMIC {
+            return Err("not a static executable");
+        }
+        if kind != PT_LOAD {
+            continue;
+        }
+        let flags = u32_at(bytes, header + 4).ok_or("truncated program header")?;
+        let offset = u64_at(bytes, header + 8).ok_or("truncated program header")? as usize;
+        let address = u64_at(bytes, header + 16).ok_or("truncated program header")?;
+        let file_size = u64_at(bytes, header + 32).ok_or("truncated program header")? as usize;
+        let size = u64_at(bytes, header + 40).ok_or("truncated program header")?;
+
+        let end = address.checked_add(size).ok_or("segment out of the user memory")?;
+        if address < USER_START || end > STACK_BOTTOM {
+            return Err("segment out of the user memory");
+        }
+        if file_size as u64 > size {
+            return Err("segment bigger in the file than in memory");
+        }
+        let data = offset.checked_add(file_size).and_then(|stop| bytes.get(offset..stop)).ok_or("segment past the end of the file")?;
+        if flags & PF_X != 0 && address <= entry && entry < end {
+            entry_found = true;
+        }
+        segments.push(Segment { address, size, data, writable: flags & PF_W != 0 });
+    }
+    if !entry_found {
+        return Err("entry point out of the code");
+    }
+    Ok(Program { entry, segments })
+}
+
+#[test_case]
+fn test_parse_rejects() {
+    assert_eq!(parse(b"#!/bin/sh").err(), Some("not an ELF file"));
+    let mut header = [0u8; HEADER_SIZE];
+    header[..4].copy_from_slice(&MAGIC);
+    header[4] = CLASS_64;
+    header[5] = LITTLE_ENDIAN;
+    header[16] = TYPE_DYN as u8;
+    header[18] = MACHINE_X86_64 as u8;
+    assert_eq!(parse(&header).err(), Some("not a static executable"));
+    header[16] = TYPE_EXEC as u8;
+    header[54] = PROGRAM_HEADER_SIZE as u8;
+    assert_eq!(parse(&header).err(), Some("entry point out of the code"));
+}
\ No newline at end of file
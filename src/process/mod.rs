@@ -0,0 +1,338 @@
+use alloc::collections::VecDeque;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::arch::asm;
+use lazy_static::lazy_static;
+use spin::Mutex;
+use x86_64::VirtAddr;
+use x86_64::structures::paging::PageTableFlags;
+use x86_64::instructions::interrupts;
+use crate::println;
+use crate::gdt;
+use crate::memory::{AddressSpace, USER_START, USER_END};
+use crate::task::thread::{self, JoinHandle};
+
+pub mod syscall;
+pub mod programs;
+pub mod elf;
+
+/*
+User processes: programs running in ring 3, each on a kernel thread of its own.
+A process has an address space of its own, see 'memory::AddressSpace':
+its program is loaded in the entry 1, from USER_START, and its stack ends at USER_END.
+It reaches the kernel only through the system calls, see syscall.rs, and a fault in it
+ends the process rather than the kernel.
+The 'exec' command which started a process serves the requests which need its shell,
+and frees the memory of the process once it is over.
+*/
+
+const STACK_SIZE: u64 = 4096 * 16;
+const STACK_BOTTOM: u64 = USER_END - STACK_SIZE;
+const MAX_ARGS: usize = 4096 * 2;   // the arguments and the environment, with their pointers
+
+#[derive(Debug, Clone)]
+pub enum Request {                  // what a process waits for its 'exec' to do
+    Open(String),                   // read a document of the shell
+    Spawn(String),                  // start a program
+}
+
+/* what is loaded: the entry point, and the bytes to copy at each address */
+pub struct Program<'a> {
+    pub entry: u64,
+    pub segments: Vec<Segment<'a>>,
+}
+
+pub struct Segment<'a> {
+    pub address: u64,
+    pub size: u64,                  // in memory, zeroed after 'data'
+    pub data: &'a [u8],
+    pub writable: bool,
+}
+
+impl<'a> Program<'a> {
+    pub fn flat(image: &'a [u8]) -> Program<'a> {   // a built-in program, run from its first byte
+        Program {
+            entry: USER_START,
+            segments: vec![Segment { address: USER_START, size: image.len() as u64, data: image, writable: false }],
+        }
+    }
+}
+
+struct Process {
+    pid: usize,
+    group: usize,                   // the pid 'exec' started, it waits for the whole group
+    space: AddressSpace,
+    ranges: Vec<(u64, u64, bool)>,  // what is mapped and if it is writable, for the checks of the system calls
+    thread: Option<JoinHandle>,
+    thread_id: usize,               // set by the thread itself before it enters ring 3
+    input: VecDeque<u8>,            // the keys, for the first process of the group
+    end_of_input: bool,             // Ctrl+D
+    files: Vec<Option<(Vec<u8>, usize)>>,   // fd 3 onwards: a document and how far it was read
+    request: Option<Request>,
+    reply: Option<Result<usize, i64>>,
+    status: Option<i64>,            // from 'exit', None if it was stopped
+}
+
+lazy_static! {
+    static ref PROCESSES: Mutex<Vec<Process>> = Mutex::new(Vec::new());
+}
+
+static mut NEXT_PID: usize = 1;
+
+/* a space with the program and its stack, where 'rsp' points at argc, argv and envp as on Linux */
+pub struct Loaded {
+    pub space: AddressSpace,
+    pub entry: u64,
+    pub rsp: u64,
+    ranges: Vec<(u64, u64, bool)>,
+}
+
+pub fn load(program: &Program, args: &[String], env: &[String]) -> Result<Loaded, &'static str> {
+    let (stack, rsp) = stack(args, env)?;
+    let mut space = AddressSpace::new().map_err(|_| "out of memory")?;
+    let mut ranges = Vec::new();
+    for segment in program.segments.iter().filter(|segment| segment.size > 0) {
+        ranges.push((segment.address & !4095, (segment.address + segment.size + 4095) & !4095, segment.writable));
+    }
+    ranges.push((STACK_BOTTOM, USER_END, true));
+    let user = PageTableFlags::PRESENT | PageTableFlags::USER_ACCESSIBLE;
+    for segment in program.segments.iter().filter(|segment| segment.size > 0) {
+        let flags = if segment.writable { user | PageTableFlags::WRITABLE } else { user };
+        space.map(VirtAddr::new(segment.address), segment.size, flags).map_err(|_| "out of memory")?;
+        space.write(VirtAddr::new(segment.address), segment.data).map_err(|_| "out of memory")?;
+    }
+    space.map(VirtAddr::new(STACK_BOTTOM), STACK_SIZE, user | PageTableFlags::WRITABLE).map_err(|_| "out of memory")?;
+    space.write(VirtAddr::new(rsp), &stack).map_err(|_| "out of memory")?;
+    Ok(Loaded { space, entry: program.entry, rsp, ranges })
+}
+
+/*
+The top of the stack: argc, the argv pointers and a NULL, the envp pointers and a NULL,
+an empty auxiliary vector, then the strings. 'rsp' is 16-byte aligned.
+*/
+fn stack(args: &[String], env: &[String]) -> Result<(Vec<u8>, u64), &'static str> {
+    let strings: usize = args.iter().chain(env.iter()).map(|s| s.len() + 1).sum();
+    let words = 1 + args.len() + 1 + env.len() + 1 + 2;
+    let size = (words * 8 + strings + 15) & !15;
+    if size > MAX_ARGS {
+        return Err("arguments too long");
+    }
+    let rsp = USER_END - size as u64;
+    let mut stack = vec![0u8; size];
+    let mut string = words * 8;
+    let mut word = 0;
+    let mut push = |stack: &mut Vec<u8>, value: u64| {
+        stack[word * 8..word * 8 + 8].copy_from_slice(&value.to_le_bytes());
+        word += 1;
+    };
+    push(&mut stack, args.len() as u64);
+    for list in [args, env].iter() {
+        for s in list.iter() {
+            push(&mut stack, rsp + string as u64);
+            stack[string..string + s.len()].copy_from_slice(s.as_bytes());
+            string += s.len() + 1;
+        }
+        push(&mut stack, 0);
+    }
+    Ok((stack, rsp))
+}
+
+/* start a loaded program printing to 'session'; 'group' is the pid of the process starting it, if any */
+pub fn start(loaded: Loaded, session: usize, group: Option<usize>) -> Result<usize, &'static str> {
+    let pid = interrupts::without_interrupts(|| unsafe {
+        let pid = NEXT_PID;
+        NEXT_PID += 1;
+        pid
+    });
+    let Loaded { space, entry, rsp, ranges } = loaded;
+    PROCESSES.lock().push(Process {
+        pid,
+        group: group.unwrap_or(pid),
+        space,
+        ranges,
+        thread: None,
+        thread_id: 0,
+        input: VecDeque::new(),
+        end_of_input: false,
+        files: Vec::new(),
+        request: None,
+        reply: None,
+        status: None,
+    });
+    match thread::spawn_thread(Some(session), move || enter(pid, entry, rsp)) {
+        Ok(handle) => {
+            if let Some(process) = PROCESSES.lock().iter_mut().find(|p| p.pid == pid) {
+                process.thread = Some(handle);
+            }
+            Ok(pid)
+        },
+        Err(_) => {
+            PROCESSES.lock().retain(|p| p.pid != pid);     // its space goes with it
+            Err("too many threads running")
+        }
+    }
+}
+
+fn enter(pid: usize, entry: u64, stack: u64) -> ! {
+    let id = thread::current_id();
+    if let Some(process) = PROCESSES.lock().iter_mut().find(|p| p.pid == pid) {
+        process.thread_id = id;
+        process.space.switch();
+    }
+    let (code, data) = gdt::user_selectors();
+    unsafe {
+        asm!(
+            "push rax",                 // SS
+            "push rsi",                 // RSP
+            "push 0x202",               // RFLAGS, interrupts on
+            "push rdx",                 // CS
+            "push rcx",                 // RIP
+            "xor eax, eax", "xor ebx, ebx", "xor ecx, ecx", "xor edx, edx",
+            "xor esi, esi", "xor edi, edi", "xor ebp, ebp",
+            "xor r8d, r8d", "xor r9d, r9d", "xor r10d, r10d", "xor r11d, r11d",
+            "xor r12d, r12d", "xor r13d, r13d", "xor r14d, r14d", "xor r15d, r15d",
+            "iretq",                    // to ring 3, nothing of the kernel left in the registers
+            in("rax") data.0 as u64,
+            in("rsi") stack,
+            in("rdx") code.0 as u64,
+            in("rcx") entry,
+            options(noreturn),
+        );
+    }
+}
+
+/* the process running on this thread, from the system calls */
+fn current<R>(f: impl FnOnce(&mut Process) -> R) -> Option<R> {
+    let id = thread::current_id();
+    PROCESSES.lock().iter_mut().find(|p| p.thread_id == id && id != 0).map(f)
+}
+
+/* if the process may hand this memory to the kernel, to read or, when 'write', to write in */
+pub fn check(address: u64, len: u64, write: bool) -> bool {
+    let end = match address.checked_add(len) {
+        Some(end) => end,
+        None => return false,
+    };
+    current(|process| process.ranges.iter().any(|(start, stop, writable)| {
+        *start <= address && end <= *stop && (*writable || !write)
+    })).unwrap_or(false)
+}
+
+pub fn exit(status: i64) -> ! {
+    current(|process| process.status = Some(status));
+    thread::exit();
+}
+
+/*
+A fault in ring 3, from the exception handlers: only the process goes.
+Interrupts come back on first, the locks taken here may be held by a thread switched out.
+*/
+pub fn fault(what: &str) -> ! {
+    interrupts::enable();
+    let pid = current(|process| process.pid).unwrap_or(0);
+    println!("\nprocess {}: {}, stopped", pid, what);
+    thread::exit();
+}
+
+/* the keys for a group, 'exec' sends them */
+pub fn give_input(group: usize, bytes: &[u8]) {
+    if let Some(process) = PROCESSES.lock().iter_mut().find(|p| p.pid == group) {
+        process.input.extend(bytes.iter());
+    }
+}
+
+pub fn end_input(group: usize) {
+    if let Some(process) = PROCESSES.lock().iter_mut().find(|p| p.pid == group) {
+        process.end_of_input = true;
+    }
+}
+
+/* Some(0) at the end of the input, None if there is nothing yet */
+pub fn read_input(buffer: &mut [u8]) -> Option<usize> {
+    current(|process| {
+        if process.input.is_empty() {
+            return if process.end_of_input { Some(0) } else { None };
+        }
+        let n = buffer.len().min(process.input.len());
+        for (i, byte) in process.input.drain(..n).enumerate() {
+            buffer[i] = byte;
+        }
+        Some(n)
+    }).flatten()
+}
+
+pub fn read_file(fd: u64, buffer: &mut [u8]) -> Result<usize, i64> {
+    current(|process| {
+        let file = match fd.checked_sub(3).and_then(|i| process.files.get_mut(i as usize)) {
+            Some(Some(file)) => file,
+            _ => return Err(syscall::EBADF),
+        };
+        let (content, at) = file;
+        let n = buffer.len().min(content.len() - *at);
+        buffer[..n].copy_from_slice(&content[*at..*at + n]);
+        *at += n;
+        Ok(n)
+    }).unwrap_or(Err(syscall::EBADF))
+}
+
+/* ask the 'exec' of the process and wait for the answer */
+pub fn request(request: Request) -> Result<usize, i64> {
+    current(|process| {
+        process.request = Some(request);
+        process.reply = None;
+    });
+    loop {
+        if let Some(reply) = current(|process| process.reply.take()).flatten() {
+            return reply;
+        }
+        if thread::cancelled() {
+            thread::exit();
+        }
+        thread::yield_now();
+    }
+}
+
+pub fn take_request(group: usize) -> Option<(usize, Request)> {
+    PROCESSES.lock().iter_mut()
+        .filter(|p| p.group == group)
+        .find_map(|p| p.request.take().map(|request| (p.pid, request)))
+}
+
+pub fn reply(pid: usize, reply: Result<usize, i64>) {
+    if let Some(process) = PROCESSES.lock().iter_mut().find(|p| p.pid == pid) {
+        process.reply = Some(reply);
+    }
+}
+
+pub fn add_file(pid: usize, content: Vec<u8>) -> Result<usize, i64> {  // the new fd
+    match PROCESSES.lock().iter_mut().find(|p| p.pid == pid) {
+        Some(process) => {
+            process.files.push(Some((content, 0)));
+            Ok(process.files.len() + 2)
+        },
+        None => Err(syscall::EBADF),
+    }
+}
+
+pub fn kill(group: usize) {         // the threads leave ring 3 at their next timer tick
+    for process in PROCESSES.lock().iter().filter(|p| p.group == group) {
+        if let Some(thread) = &process.thread {
+            thread.cancel();
+        }
+    }
+}
+
+pub fn finished(group: usize) -> bool {
+    PROCESSES.lock().iter()
+        .filter(|p| p.group == group)
+        .all(|p| p.thread.as_ref().map_or(true, |thread| thread.is_finished()))
+}
+
+/* once 'finished': free the memory of the group, the status of its first process */
+pub fn reap(group: usize) -> Option<i64> {
+    let mut processes = PROCESSES.lock();
+    let status = processes.iter().find(|p| p.pid == group).and_then(|p| p.status);
+    processes.retain(|p| p.group != group);     // their spaces go with them
+    status
+}
\ No newline at end of file
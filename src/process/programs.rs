@@ -0,0 +1,109 @@
+use core::arch::global_asm;
+
+/*
+The built-in programs of 'exec', flat position-independent images run from their first byte.
+They are kept as data, the kernel copies them into the memory of a process.
+*/
+global_asm!(
+    ".pushsection .rodata",
+
+    ".global PROGRAM_HELLO",        // print a line
+    "PROGRAM_HELLO:",
+    "mov eax, 1",
+    "mov edi, 1",
+    "lea rsi, [rip + .Lhello_text]",
+    "lea rdx, [rip + .Lhello_end]",
+    "sub rdx, rsi",
+    "int 0x80",
+    "xor eax, eax",
+    "xor edi, edi",
+    "int 0x80",
+    ".Lhello_text:",
+    ".ascii \"Hello from ring 3!\\n\"",
+    ".Lhello_end:",
+    ".global PROGRAM_HELLO_END",
+    "PROGRAM_HELLO_END:",
+
+    ".global PROGRAM_ECHO",         // print back what is typed, until Ctrl+D
+    "PROGRAM_ECHO:",
+    "sub rsp, 256",
+    ".Lecho_loop:",
+    "mov eax, 2",
+    "xor edi, edi",
+    "mov rsi, rsp",
+    "mov edx, 256",
+    "int 0x80",
+    "test rax, rax",
+    "jle .Lecho_end",
+    "mov rdx, rax",
+    "mov eax, 1",
+    "mov edi, 1",
+    "mov rsi, rsp",
+    "int 0x80",
+    "jmp .Lecho_loop",
+    ".Lecho_end:",
+    "xor eax, eax",
+    "xor edi, edi",
+    "int 0x80",
+    ".global PROGRAM_ECHO_END",
+    "PROGRAM_ECHO_END:",
+
+    ".global PROGRAM_CRASH",        // write to the VGA buffer, which belongs to the kernel
+    "PROGRAM_CRASH:",
+    "mov eax, 0xb8000",
+    "mov byte ptr [rax], 0x41",
+    "xor eax, eax",
+    "xor edi, edi",
+    "int 0x80",
+    ".global PROGRAM_CRASH_END",
+    "PROGRAM_CRASH_END:",
+
+    ".global PROGRAM_SPIN",         // never ends, for Ctrl+C
+    "PROGRAM_SPIN:",
+    "jmp PROGRAM_SPIN",
+    ".global PROGRAM_SPIN_END",
+    "PROGRAM_SPIN_END:",
+
+    ".popsection",
+);
+
+extern "C" {
+    static PROGRAM_HELLO: u8;
+    static PROGRAM_HELLO_END: u8;
+    static PROGRAM_ECHO: u8;
+    static PROGRAM_ECHO_END: u8;
+    static PROGRAM_CRASH: u8;
+    static PROGRAM_CRASH_END: u8;
+    static PROGRAM_SPIN: u8;
+    static PROGRAM_SPIN_END: u8;
+}
+
+pub const NAMES: [&str; 4] = ["hello", "echo", "crash", "spin"];
+
+/* the ELF executables built from process/sample, kept once in the kernel and parsed when 'exec' runs them */
+pub const EXECUTABLES: [&str; 1] = ["hello.elf"];
+static HELLO_ELF: &[u8] = include_bytes!("sample/hello");
+
+fn image(start: &'static u8, end: &'static u8) -> &'static [u8] {
+    let start = start as *const u8;
+    unsafe { core::slice::from_raw_parts(start, end as *const u8 as usize - start as usize) }
+}
+
+pub fn find(name: &str) -> Option<&'static [u8]> {
+    unsafe {
+        match name {
+            "hello" => Some(image(&PROGRAM_HELLO, &PROGRAM_HELLO_END)),
+            "echo" => Some(image(&PROGRAM_ECHO, &PROGRAM_ECHO_END)),
+            "crash" => Some(image(&PROGRAM_CRASH, &PROGRAM_CRASH_END)),
+            "spin" => Some(image(&PROGRAM_SPIN, &PROGRAM_SPIN_END)),
+            _ => None,
+        }
+    }
+}
+
+pub fn find_executable(name: &str) -> Option<&'static [u8]> {
+    match name {
+        "hello.elf" => Some(HELLO_ELF),
+        _ => None,
+    }
+}
\ No newline at end of file
@@ -0,0 +1,9 @@
+#!/bin/sh
+# Build the sample program installed in root\bin with the host binutils, and commit the result.
+# It is linked in the user part of the address space, see memory::USER_START.
+set -e
+cd "$(dirname "$0")"
+as --64 -o hello.o hello.s
+ld -static -nostdlib -s --build-id=none -z noexecstack -z max-page-size=4096 \
+    -Ttext-segment=0x8000000000 -o hello hello.o
+rm hello.o
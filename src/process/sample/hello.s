@@ -0,0 +1,70 @@
+# A static ELF64 program for 'exec', built on the host with build.sh.
+# It prints its arguments and environment through the system calls of
+# ../syscall.rs, and exits with argc as its status.
+
+    .intel_syntax noprefix
+    .globl _start
+
+    .text
+_start:
+    mov r12, [rsp]                  # argc, then argv, a NULL, envp and a NULL
+    lea r13, [rsp + 8]
+    mov [rip + argc], r12           # through .bss, which must be writable and zeroed
+    lea rdi, [rip + greeting]
+    call puts
+
+    xor ebx, ebx
+1:  cmp rbx, r12
+    jae 2f
+    lea rdi, [rip + arg]
+    call puts
+    mov rdi, [r13 + rbx * 8]
+    call puts
+    lea rdi, [rip + newline]
+    call puts
+    inc rbx
+    jmp 1b
+
+2:  lea r14, [r13 + r12 * 8 + 8]
+3:  cmp qword ptr [r14], 0
+    je 4f
+    lea rdi, [rip + env]
+    call puts
+    mov rdi, [r14]
+    call puts
+    lea rdi, [rip + newline]
+    call puts
+    add r14, 8
+    jmp 3b
+
+4:  xor eax, eax                    # exit(argc)
+    mov rdi, [rip + argc]
+    int 0x80
+
+# write(1, rdi, strlen(rdi))
+puts:
+    mov rsi, rdi
+    xor edx, edx
+5:  cmp byte ptr [rsi + rdx], 0
+    je 6f
+    inc rdx
+    jmp 5b
+6:  mov eax, 1
+    mov edi, 1
+    int 0x80
+    ret
+
+    .section .rodata
+greeting:
+    .asciz "Hello from an ELF executable!\n"
+arg:
+    .asciz "  arg "
+env:
+    .asciz "  env "
+newline:
+    .asciz "\n"
+
+    .bss
+    .balign 8
+argc:
+    .skip 8
@@ -0,0 +1,163 @@
+use alloc::string::String;
+use core::arch::global_asm;
+use x86_64::instructions::interrupts;
+use crate::print;
+use crate::interrupts::TIMER_COUNT;
+use crate::task::thread;
+use super::Request;
+
+/*
+The system calls, 'int 0x80' from ring 3:
+    rax             the number of the call
+    rdi, rsi, rdx   its arguments
+    rax             the result, a negative error for a failure
+The other registers are kept.
+
+    0  exit(status)             end the process, never returns
+    1  write(fd, buf, len)      fd 1 and 2 print to the terminal of the process, returns len
+    2  read(fd, buf, len)       fd 0 waits for keys and returns what was typed, without echo;
+                                fd 3 onwards reads an opened document; 0 at the end, Ctrl+D for the keys
+    3  open(path, len)          a document of the shell which started the process, read-only, returns its fd
+    4  sleep(ms)                at least 'ms', in timer ticks of about 50 ms, returns 0
+    5  spawn(name, len)         start another program on the same terminal, built-in or in PATH,
+                                without arguments, returns its pid
+
+Strings are UTF-8 and not terminated. Every pointer is checked to lie in the memory of
+the process, and in writable memory for what the kernel writes in, e.g. the buffer of 'read':
+the kernel returns EFAULT rather than follow one which doesn't.
+*/
+
+pub const VECTOR: usize = 0x80;
+
+pub const ENOENT: i64 = -2;
+pub const EBADF: i64 = -9;
+pub const EAGAIN: i64 = -11;
+pub const EFAULT: i64 = -14;
+pub const EINVAL: i64 = -22;
+pub const ENOSYS: i64 = -38;
+
+const MAX_PATH: u64 = 256;
+
+/* same as 'thread::timer_entry', the handler changes the saved rax */
+global_asm!(
+    ".global syscall_entry",
+    "syscall_entry:",
+    "push rax", "push rbx", "push rcx", "push rdx", "push rsi", "push rdi", "push rbp",
+    "push r8", "push r9", "push r10", "push r11", "push r12", "push r13", "push r14", "push r15",
+    "cld",
+    "mov rdi, rsp",
+    "call syscall_handler",
+    "pop r15", "pop r14", "pop r13", "pop r12", "pop r11", "pop r10", "pop r9", "pop r8",
+    "pop rbp", "pop rdi", "pop rsi", "pop rdx", "pop rcx", "pop rbx", "pop rax",
+    "iretq",
+);
+
+extern "C" {
+    pub fn syscall_entry();
+}
+
+#[repr(C)]
+#[allow(dead_code)]
+struct Registers {                  // as pushed by 'syscall_entry'
+    r15: u64, r14: u64, r13: u64, r12: u64, r11: u64, r10: u64, r9: u64, r8: u64,
+    rbp: u64, rdi: u64, rsi: u64, rdx: u64, rcx: u64, rbx: u64, rax: u64,
+}
+
+#[no_mangle]
+extern "C" fn syscall_handler(registers: &mut Registers) {
+    interrupts::enable();           // a call may wait, the other threads go on meanwhile
+    let result = match registers.rax {
+        0 => super::exit(registers.rdi as i64),
+        1 => write(registers.rdi, registers.rsi, registers.rdx),
+        2 => read(registers.rdi, registers.rsi, registers.rdx),
+        3 => open(registers.rdi, registers.rsi),
+        4 => sleep(registers.rdi),
+        5 => spawn(registers.rdi, registers.rsi),
+        _ => ENOSYS,
+    };
+    interrupts::disable();
+    registers.rax = result as u64;
+}
+
+fn user_bytes<'a>(address: u64, len: u64) -> Result<&'a [u8], i64> {
+    if !super::check(address, len, false) {
+        return Err(EFAULT);
+    }
+    Ok(unsafe { core::slice::from_raw_parts(address as *const u8, len as usize) })
+}
+
+fn user_buffer<'a>(address: u64, len: u64) -> Result<&'a mut [u8], i64> {   // for the kernel to write in
+    if !super::check(address, len, true) {
+        return Err(EFAULT);
+    }
+    Ok(unsafe { core::slice::from_raw_parts_mut(address as *mut u8, len as usize) })
+}
+
+fn user_str(address: u64, len: u64) -> Result<String, i64> {
+    if len > MAX_PATH {
+        return Err(EINVAL);
+    }
+    let bytes = user_bytes(address, len)?;
+    String::from_utf8(bytes.to_vec()).map_err(|_| EINVAL)
+}
+
+fn write(fd: u64, address: u64, len: u64) -> i64 {
+    if fd != 1 && fd != 2 {
+        return EBADF;
+    }
+    match user_bytes(address, len) {
+        Ok(bytes) => {
+            print!("{}", String::from_utf8_lossy(bytes));
+            len as i64
+        },
+        Err(error) => error,
+    }
+}
+
+fn read(fd: u64, address: u64, len: u64) -> i64 {
+    let buffer = match user_buffer(address, len) {
+        Ok(buffer) => buffer,
+        Err(error) => return error,
+    };
+    if fd >= 3 {
+        return super::read_file(fd, buffer).map_or_else(|error| error, |n| n as i64);
+    }
+    if fd != 0 {
+        return EBADF;
+    }
+    loop {                          // the memory stays mapped while the thread lives
+        if let Some(n) = super::read_input(buffer) {
+            return n as i64;
+        }
+        if thread::cancelled() {
+            thread::exit();
+        }
+        thread::yield_now();
+    }
+}
+
+fn open(address: u64, len: u64) -> i64 {
+    match user_str(address, len) {
+        Ok(path) => super::request(Request::Open(path)).map_or_else(|error| error, |fd| fd as i64),
+        Err(error) => error,
+    }
+}
+
+fn sleep(ms: u64) -> i64 {
+    let ticks = ((ms + 49) / 50).max(1);
+    let until = unsafe { TIMER_COUNT } + ticks;
+    while unsafe { TIMER_COUNT } < until {
+        if thread::cancelled() {
+            thread::exit();
+        }
+        thread::yield_now();
+    }
+    0
+}
+
+fn spawn(address: u64, len: u64) -> i64 {
+    match user_str(address, len) {
+        Ok(name) => super::request(Request::Spawn(name)).map_or_else(|error| error, |pid| pid as i64),
+        Err(error) => error,
+    }
+}
\ No newline at end of file
@@ -1,6 +1,15 @@
 use uart_16550::SerialPort;
 use spin::Mutex;
 use lazy_static::lazy_static;
+use conquer_once::spin::OnceCell;
+use crossbeam_queue::ArrayQueue;
+use core::{fmt, pin::Pin, task::{Poll, Context}};
+use futures_util::stream::{Stream, StreamExt};
+use futures_util::task::AtomicWaker;
+use x86_64::instructions::port::Port;
+use crate::println;
+use crate::terminal::session::{self, SERIAL};
+use crate::terminal::jobs;
 
 lazy_static! {
     pub static ref SERIAL1: Mutex<SerialPort> = {
@@ -23,6 +32,113 @@ pub fn _print(args: ::core::fmt::Arguments) {
     })
 }
 
+/*
+The output of the shell on COM1, see 'session::run_serial'.
+The private bytes of the VGA writer become what a VT100 terminal understands.
+*/
+pub fn _print_console(args: fmt::Arguments) {
+    use core::fmt::Write;
+    use x86_64::instructions::interrupts;
+
+    interrupts::without_interrupts(|| {
+        let mut port = SERIAL1.lock();
+        let _ = Console(|byte| port.send(byte)).write_fmt(args);
+    })
+}
+
+struct Console<F: FnMut(u8)>(F);      // what to do with each byte to send
+
+impl<F: FnMut(u8)> fmt::Write for Console<F> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for byte in s.bytes() {
+            match byte {
+                b'\n' => {
+                    (self.0)(b'\r');
+                    (self.0)(b'\n');
+                },
+                0x08 => {                       // sent as "\x08 \x08", it erases like the VGA backspace
+                    for byte in b"\x08 \x08" {
+                        (self.0)(*byte);
+                    }
+                },
+                byte => (self.0)(byte),
+            }
+        }
+        Ok(())
+    }
+}
+
+static BYTE_QUEUE: OnceCell<ArrayQueue<u8>> = OnceCell::uninit();
+static WAKER: AtomicWaker = AtomicWaker::new();
+
+pub fn init() {                     // receive interrupts on IRQ4 for the shell on COM1
+    lazy_static::initialize(&SERIAL1);
+    unsafe {
+        Port::<u8>::new(0x3F9).write(0x01);    // interrupt when a byte is received
+        Port::<u8>::new(0x3FC).write(0x0b);    // DTR, RTS and OUT2, which lets the interrupt reach the PIC
+        let mut mask = Port::<u8>::new(0x21);  // unmask IRQ4 on the primary PIC
+        let masks: u8 = mask.read();
+        mask.write(masks & !(1 << 4));
+    }
+}
+
+/* called by the serial interrupt handler, must not block or allocate */
+pub(crate) fn add_byte(byte: u8) {
+    if let Ok(queue) = BYTE_QUEUE.try_get() {
+        if let Err(_) = queue.push(byte) {
+            println!("WARNING: serial queue full; dropping serial input");
+        } else {
+            WAKER.wake();
+        }
+    }
+}
+
+struct ByteStream {
+    _private: (),
+}
+
+impl ByteStream {
+    fn new() -> Self {
+        BYTE_QUEUE.try_init_once(|| ArrayQueue::new(100))
+            .expect("ByteStream::new should only be called once");
+        ByteStream { _private: () }
+    }
+}
+
+impl Stream for ByteStream {
+    type Item = u8;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<u8>> {
+        let queue = BYTE_QUEUE.try_get().expect("not initialized");
+
+        if let Ok(byte) = queue.pop() {
+            return Poll::Ready(Some(byte));
+        }
+        WAKER.register(&cx.waker());
+        match queue.pop() {
+            Ok(byte) => {
+                WAKER.take();
+                Poll::Ready(Some(byte))
+            }
+            Err(crossbeam_queue::PopError) => Poll::Pending,
+        }
+    }
+}
+
+/*
+The bytes received on COM1, handed to the serial session like the keyboard task does for the VGA ones.
+Ctrl+C reaches a running command here, while the session reads no input.
+*/
+pub async fn read_input() {
+    let mut bytes = ByteStream::new();
+    while let Some(byte) = bytes.next().await {
+        if byte == 0x03 {
+            jobs::interrupt(SERIAL);
+        }
+        session::add_serial_byte(byte);
+    }
+}
+
 // Prints to the host through the serial interface
 #[macro_export]
 macro_rules! serial_print {
@@ -38,4 +154,14 @@ macro_rules! serial_println {
     ($fmt:expr) => ($crate::serial_print!(concat!($fmt, "\n")));
     ($fmt:expr, $($arg:tt)*) => ($crate::serial_print!(
         concat!($fmt, "\n"), $($arg)*));
-}
\ No newline at end of file
+}
+
+#[test_case]
+fn test_console_bytes() {
+    use alloc::vec::Vec;
+    use core::fmt::Write;
+
+    let mut sent: Vec<u8> = Vec::new();
+    write!(Console(|byte| sent.push(byte)), "ab\n\x1b[2D\x1b[J\x09\x08").unwrap();
+    assert_eq!(sent, b"ab\r\n\x1b[2D\x1b[J\x09\x08 \x08");   // the escapes are the same for a VT100
+}
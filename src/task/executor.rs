@@ -1,8 +1,22 @@
 use super::{Task, TaskId};
-use alloc::{collections::BTreeMap, sync::Arc, task::Wake};
+use super::thread;
+use alloc::{collections::BTreeMap, sync::Arc, task::Wake, vec::Vec};
 use core::task::{Waker, Context, Poll};
 use crossbeam_queue::ArrayQueue;
 
+static mut SPAWNED: Vec<Task> = Vec::new();    // tasks spawned by other tasks, picked up by the running executor
+static mut TASKS: usize = 0;                    // the tasks of the running executor, for the status bar
+
+pub fn spawn(task: Task) {      // for tasks, which can't reach the executor running them
+    unsafe {
+        SPAWNED.push(task);
+    }
+}
+
+pub fn task_count() -> usize {
+    unsafe { TASKS }
+}
+
 pub struct Executor {
     tasks: BTreeMap<TaskId, Task>,
     task_queue: Arc<ArrayQueue<TaskId>>,
@@ -24,6 +38,9 @@ impl Executor {
             panic!("task with same ID already in tasks");
         }
         self.task_queue.push(task_id).expect("queue full");
+        unsafe {
+            TASKS = self.tasks.len();
+        }
     }
 
     fn run_ready_tasks(&mut self) -> bool {
@@ -46,6 +63,9 @@ impl Executor {
                 Poll::Ready(()) => {
                     tasks.remove(&task_id);
                     waker_cache.remove(&task_id);
+                    unsafe {
+                        TASKS = tasks.len();
+                    }
                     return true;
                 }
                 Poll::Pending => {}
@@ -54,14 +74,23 @@ impl Executor {
         return false;
     }
 
+    fn spawn_pending(&mut self) {
+        let spawned = unsafe { core::mem::take(&mut SPAWNED) };
+        for task in spawned {
+            self.spawn(task);
+        }
+    }
+
     pub fn run(&mut self) -> ! {
         loop {
+            self.spawn_pending();
             self.run_ready_tasks();
             self.sleep_if_idle();
         }
     }
     pub fn run_once(&mut self) {
         loop {
+            self.spawn_pending();
             if self.run_ready_tasks() == true {
                 break;
             }
@@ -73,7 +102,12 @@ impl Executor {
         use x86_64::instructions::interrupts::{self, enable_and_hlt};
 
         interrupts::disable();
-        if self.task_queue.is_empty() {
+        if self.task_queue.is_empty() && unsafe { SPAWNED.is_empty() } {
+            if thread::others_ready() {     // nothing to poll, let the threads run instead
+                interrupts::enable();
+                thread::yield_now();
+                return;
+            }
             enable_and_hlt();       // make enable interrupts and hlt to a single atomic operation
         } else {
             interrupts::enable();
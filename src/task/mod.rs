@@ -6,6 +6,7 @@ use alloc::boxed::Box;
 pub mod simple_executor;
 pub mod executor;
 pub mod async_task;
+pub mod thread;
 
 pub struct Task {
     id: TaskId,
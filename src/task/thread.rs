@@ -0,0 +1,350 @@
+use alloc::boxed::Box;
+use alloc::sync::Arc;
+use alloc::vec;
+use core::arch::{asm, global_asm};
+use core::sync::atomic::{AtomicBool, Ordering};
+use conquer_once::spin::OnceCell;
+use spin::Mutex;
+use x86_64::instructions::interrupts;
+use x86_64::VirtAddr;
+use x86_64::registers::control::Cr3;
+use x86_64::structures::paging::PhysFrame;
+use crate::gdt;
+
+/*
+Kernel threads, preempted by the timer. Each has its own stack: the timer interrupt saves the
+registers of the running thread on its stack, and goes on with the next ready one once its
+time slice is over, round-robin.
+Thread 0 is the kernel itself, running the async executor on the boot stack, so the tasks
+keep going while a thread is busy, e.g. a script in a 'while true' loop.
+The stack of a thread is also where the CPU goes when it leaves ring 3, see 'process'.
+A thread must not be switched out holding a lock the others take with interrupts off:
+the heap allocator takes its lock with interrupts off for that reason.
+*/
+
+pub const MAX_THREADS: usize = 8;
+pub const YIELD_VECTOR: usize = 0x81;   // 'int 0x81' gives up the rest of the time slice
+const STACK_SIZE: usize = 4096 * 16;    // 64 KiB, taken from the heap
+const SLICE: u64 = 2;                   // the timer ticks a thread runs before the next one, about 0.1 s
+
+/*
+The entries of the timer interrupt and of 'int 0x81': push the registers the CPU didn't,
+hand the stack to the handler and pop the registers of the thread whose stack it returns.
+The CPU pushed SS, RSP, RFLAGS, CS and RIP, and 'iretq' takes them back.
+*/
+global_asm!(
+    ".global timer_entry",
+    "timer_entry:",
+    "push rax", "push rbx", "push rcx", "push rdx", "push rsi", "push rdi", "push rbp",
+    "push r8", "push r9", "push r10", "push r11", "push r12", "push r13", "push r14", "push r15",
+    "cld",                          // the Rust code expects DF clear, whatever ring 3 left there
+    "mov rdi, rsp",
+    "call timer_interrupt_handler",
+    "mov rsp, rax",
+    "pop r15", "pop r14", "pop r13", "pop r12", "pop r11", "pop r10", "pop r9", "pop r8",
+    "pop rbp", "pop rdi", "pop rsi", "pop rdx", "pop rcx", "pop rbx", "pop rax",
+    "iretq",
+    ".global yield_entry",
+    "yield_entry:",
+    "push rax", "push rbx", "push rcx", "push rdx", "push rsi", "push rdi", "push rbp",
+    "push r8", "push r9", "push r10", "push r11", "push r12", "push r13", "push r14", "push r15",
+    "cld",
+    "mov rdi, rsp",
+    "call yield_interrupt_handler",
+    "mov rsp, rax",
+    "pop r15", "pop r14", "pop r13", "pop r12", "pop r11", "pop r10", "pop r9", "pop r8",
+    "pop rbp", "pop rdi", "pop rsi", "pop rdx", "pop rcx", "pop rbx", "pop rax",
+    "iretq",
+);
+
+extern "C" {
+    pub fn timer_entry();
+    pub fn yield_entry();
+}
+
+const SAVED: usize = 15;            // the registers pushed by the entries, r15 at the bottom and rax at the top
+const RDI: usize = 9;
+
+struct Control {                    // shared with the JoinHandle
+    finished: AtomicBool,
+    cancelled: AtomicBool,
+}
+
+struct Thread {
+    id: usize,
+    rsp: u64,                       // where its registers are saved, while it doesn't run
+    _stack: Option<Box<[u64]>>,     // None for thread 0, which runs on the boot stack
+    stack_top: u64,                 // 16-byte aligned, 0 for thread 0
+    table: PhysFrame,               // its level 4 page table, the kernel's unless it runs a process
+    session: Option<usize>,         // the session it prints to
+    control: Arc<Control>,
+}
+
+struct Scheduler {
+    threads: [Option<Thread>; MAX_THREADS],
+    current: usize,                 // the slot running
+    ticks: u64,                     // of its time slice
+    next_id: usize,
+    kernel_table: PhysFrame,
+}
+
+static SCHEDULER: OnceCell<Mutex<Scheduler>> = OnceCell::uninit();
+
+pub fn init() {                     // once the heap is there, the kernel becomes thread 0
+    SCHEDULER.try_init_once(|| {
+        let mut threads: [Option<Thread>; MAX_THREADS] = Default::default();
+        let (kernel_table, _) = Cr3::read();
+        threads[0] = Some(Thread {
+            id: 0,
+            rsp: 0,
+            _stack: None,
+            stack_top: 0,
+            table: kernel_table,
+            session: None,
+            control: Arc::new(Control { finished: AtomicBool::new(false), cancelled: AtomicBool::new(false) }),
+        });
+        Mutex::new(Scheduler { threads, current: 0, ticks: 0, next_id: 1, kernel_table })
+    }).expect("thread::init should only be called once");
+}
+
+/* with interrupts off, so that the timer never finds the lock taken */
+fn with_scheduler<R>(f: impl FnOnce(&mut Scheduler) -> R) -> Option<R> {
+    interrupts::without_interrupts(|| {
+        match SCHEDULER.try_get() {
+            Ok(scheduler) => scheduler.try_lock().map(|mut scheduler| f(&mut scheduler)),
+            Err(_) => None,
+        }
+    })
+}
+
+impl Scheduler {
+    fn switch(&mut self, rsp: u64, yielding: bool) -> u64 {    // the stack to go on with
+        if let Some(thread) = &mut self.threads[self.current] {
+            thread.rsp = rsp;
+        }
+        self.ticks += 1;
+        if yielding || self.ticks >= SLICE {
+            self.ticks = 0;
+            for step in 1..=MAX_THREADS {
+                let slot = (self.current + step) % MAX_THREADS;
+                if let Some(thread) = &self.threads[slot] {
+                    if !thread.control.finished.load(Ordering::SeqCst) {
+                        self.current = slot;
+                        break;
+                    }
+                }
+            }
+        }
+        let thread = self.current();
+        let (active, flags) = Cr3::read();
+        if active != thread.table {
+            unsafe {
+                Cr3::write(thread.table, flags);
+            }
+        }
+        if thread.stack_top != 0 {
+            gdt::set_kernel_stack(VirtAddr::new(thread.stack_top));
+            if thread.control.cancelled.load(Ordering::SeqCst) {
+                unsafe {
+                    leave_user(thread.rsp, thread.stack_top);
+                }
+            }
+        }
+        thread.rsp
+    }
+
+    fn current(&self) -> &Thread {
+        self.threads[self.current].as_ref().expect("the running thread has no slot")
+    }
+}
+
+/* from the timer interrupt, after its end of interrupt was sent */
+pub fn switch(rsp: u64) -> u64 {
+    with_scheduler(|scheduler| scheduler.switch(rsp, false)).unwrap_or(rsp)
+}
+
+/*
+A thread cancelled while in ring 3 goes on in 'exit' instead of where it was:
+a program is not trusted to stop by itself.
+*/
+unsafe fn leave_user(rsp: u64, stack_top: u64) {
+    let frame = (rsp as *mut u64).add(SAVED);
+    if *frame.add(1) & 3 != 3 {     // CS, the thread is in the kernel
+        return;
+    }
+    let (code, data) = gdt::kernel_selectors();
+    *frame = exit as usize as u64;
+    *frame.add(1) = code.0 as u64;
+    *frame.add(2) = 0x202;
+    *frame.add(3) = stack_top - 8;
+    *frame.add(4) = data.0 as u64;
+}
+
+#[no_mangle]
+extern "C" fn yield_interrupt_handler(rsp: u64) -> u64 {
+    with_scheduler(|scheduler| scheduler.switch(rsp, true)).unwrap_or(rsp)
+}
+
+pub fn yield_now() {
+    unsafe {
+        asm!("int 0x81");
+    }
+}
+
+pub struct JoinHandle<T = ()> {
+    id: usize,
+    control: Arc<Control>,
+    result: Arc<Mutex<Option<T>>>,  // set by the thread before it is marked finished
+}
+
+impl<T> JoinHandle<T> {
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.control.finished.load(Ordering::SeqCst)
+    }
+
+    pub fn cancel(&self) {          // nothing is stopped by force, the thread checks 'cancelled'
+        self.control.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    pub fn join(self) -> Option<T> {    // from another thread, None if it exited early; a task awaits 'is_finished' instead, see 'run'
+        while !self.is_finished() {
+            yield_now();
+        }
+        self.result.lock().take()
+    }
+}
+
+/*
+Start 'entry' in a new thread, printing to 'session' if given.
+Threads which finished are dropped first, so that their slots and stacks can be reused.
+*/
+pub fn spawn_thread<T, F>(session: Option<usize>, entry: F) -> Result<JoinHandle<T>, ()>
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    reap();
+    let table = with_scheduler(|scheduler| scheduler.kernel_table).ok_or(())?;    // a process sets its own, see 'set_table'
+    let result = Arc::new(Mutex::new(None));
+    let slot = result.clone();
+    let entry = move || {
+        let value = entry();
+        *slot.lock() = Some(value);
+    };
+    let entry: Box<Box<dyn FnOnce() + Send>> = Box::new(Box::new(entry));
+    let entry = Box::into_raw(entry);
+    let mut stack = vec![0u64; STACK_SIZE / 8].into_boxed_slice();
+    let (code, data) = gdt::kernel_selectors();
+
+    /* the stack as if the thread was interrupted right before 'thread_start' */
+    let base = stack.as_ptr() as u64;
+    let stack_top = (base + STACK_SIZE as u64) & !0xf;
+    let top = ((stack_top - base) / 8) as usize;
+    let frame = top - 1 - 5 - SAVED;    // below the return address 'thread_start' doesn't have
+    stack[frame + RDI] = entry as u64;
+    stack[frame + SAVED] = thread_start as usize as u64;   // RIP
+    stack[frame + SAVED + 1] = code.0 as u64;
+    stack[frame + SAVED + 2] = 0x202;                      // RFLAGS, interrupts on
+    stack[frame + SAVED + 3] = stack_top - 8;              // RSP, as after a call
+    stack[frame + SAVED + 4] = data.0 as u64;
+    let rsp = &stack[frame] as *const u64 as u64;
+
+    let control = Arc::new(Control { finished: AtomicBool::new(false), cancelled: AtomicBool::new(false) });
+    let handle = JoinHandle { id: 0, control: control.clone(), result };
+    let mut thread = Some(Thread { id: 0, rsp, _stack: Some(stack), stack_top, table, session, control });
+    let id = with_scheduler(|scheduler| {
+        let slot = scheduler.threads.iter().position(|thread| thread.is_none())?;
+        let id = scheduler.next_id;
+        scheduler.next_id += 1;
+        let mut thread = thread.take().unwrap();
+        thread.id = id;
+        scheduler.threads[slot] = Some(thread);
+        Some(id)
+    }).flatten();
+    match id {
+        Some(id) => Ok(JoinHandle { id, ..handle }),
+        None => {
+            unsafe {
+                drop(Box::from_raw(entry));
+            }
+            Err(())
+        }
+    }
+}
+
+extern "C" fn thread_start(entry: *mut Box<dyn FnOnce() + Send>) -> ! {
+    let entry = unsafe { Box::from_raw(entry) };
+    entry();
+    exit();
+}
+
+pub extern "C" fn exit() -> ! {     // the running thread is over
+    with_scheduler(|scheduler| scheduler.current().control.finished.store(true, Ordering::SeqCst));
+    loop {                          // never chosen again, 'reap' frees the stack
+        yield_now();
+    }
+}
+
+fn reap() {
+    let mut finished: [Option<Thread>; MAX_THREADS] = Default::default();
+    with_scheduler(|scheduler| {
+        for slot in 0..MAX_THREADS {
+            let done = match &scheduler.threads[slot] {
+                Some(thread) => slot != scheduler.current && thread.control.finished.load(Ordering::SeqCst),
+                None => false,
+            };
+            if done {
+                finished[slot] = scheduler.threads[slot].take();
+            }
+        }
+    });
+    drop(finished);                 // the stacks are freed with interrupts on
+}
+
+/* a process's page table for the running thread, from now on and after each switch back to it */
+pub fn set_table(table: PhysFrame) {
+    interrupts::without_interrupts(|| {
+        with_scheduler(|scheduler| {
+            let current = scheduler.current;
+            if let Some(thread) = &mut scheduler.threads[current] {
+                thread.table = table;
+            }
+        });
+        let (_, flags) = Cr3::read();
+        unsafe {
+            Cr3::write(table, flags);
+        }
+    });
+}
+
+pub fn current_id() -> usize {
+    with_scheduler(|scheduler| scheduler.current().id).unwrap_or(0)
+}
+
+pub fn session() -> Option<usize> { // where the running thread prints, None for thread 0
+    with_scheduler(|scheduler| scheduler.current().session).flatten()
+}
+
+pub fn cancelled() -> bool {        // if the running thread was asked to stop
+    with_scheduler(|scheduler| scheduler.current().control.cancelled.load(Ordering::SeqCst)).unwrap_or(false)
+}
+
+pub fn others_ready() -> bool {     // threads other than the running one want the CPU
+    with_scheduler(|scheduler| {
+        let current = scheduler.current;
+        scheduler.threads.iter().enumerate().any(|(slot, thread)| match thread {
+            Some(thread) => slot != current && !thread.control.finished.load(Ordering::SeqCst),
+            None => false,
+        })
+    }).unwrap_or(false)
+}
+
+pub fn count() -> usize {           // the threads besides thread 0
+    with_scheduler(|scheduler| {
+        scheduler.threads.iter().flatten().filter(|thread| thread.id != 0 && !thread.control.finished.load(Ordering::SeqCst)).count()
+    }).unwrap_or(0)
+}
\ No newline at end of file
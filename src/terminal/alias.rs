@@ -0,0 +1,111 @@
+use alloc::vec::Vec;
+use alloc::string::String;
+use super::env::{Environment, valid_name};
+use super::tokenizer::{tokenize, Token, TokenizeError, Operator};
+
+#[derive(Clone)]
+pub struct Aliases {                // alias name='cmd args' of the shell running in one terminal
+    aliases: Vec<(String, String)>,
+}
+
+impl Aliases {
+    pub fn new() -> Aliases {
+        Aliases {
+            aliases: Vec::new(),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.aliases.iter().find(|(n, _)| n == name).map(|(_, v)| v.as_str())
+    }
+
+    pub fn set(&mut self, name: &str, value: &str) -> Result<(), ()> {
+        if !valid_name(name) {
+            return Err(());
+        }
+        match self.aliases.iter_mut().find(|(n, _)| n == name) {
+            Some((_, v)) => *v = String::from(value),
+            None => self.aliases.push((String::from(name), String::from(value))),
+        }
+        Ok(())
+    }
+
+    pub fn remove(&mut self, name: &str) -> Result<(), ()> {
+        match self.aliases.iter().position(|(n, _)| n == name) {
+            Some(index) => {
+                self.aliases.remove(index);
+                Ok(())
+            },
+            None => Err(()),
+        }
+    }
+
+    pub fn aliases(&self) -> &Vec<(String, String)> {
+        &self.aliases
+    }
+
+    /*
+    Replace the unquoted first word of every command by the tokens of its alias.
+    The result is not expanded again, so 'alias ls='ls -x'' does not loop.
+    */
+    pub fn expand(&self, tokens: Vec<Token>, env: &Environment) -> Result<Vec<Token>, TokenizeError> {
+        let mut expanded: Vec<Token> = Vec::new();
+        let mut command = true;         // the next word is a command name
+        let mut file = false;           // the next word is the file of a redirection
+
+        for token in tokens {
+            match token {
+                Token::Word(word) => {
+                    if command && !file && !word.quoted {
+                        if let Some(value) = self.get(&word.text) {
+                            let value: Vec<char> = value.chars().collect();
+                            expanded.extend(tokenize(&value, env)?);
+                            command = false;
+                            continue;
+                        }
+                    }
+                    if !file {
+                        command = false;
+                    }
+                    file = false;
+                    expanded.push(Token::Word(word));
+                },
+                Token::Operator(operator) => {
+                    if operator == Operator::Pipe {
+                        command = true;
+                    } else if operator != Operator::Background {
+                        file = true;
+                    }
+                    expanded.push(Token::Operator(operator));
+                }
+            }
+        }
+        return Ok(expanded);
+    }
+}
+
+#[test_case]
+fn test_alias_expand() {
+    use alloc::format;
+    use crate::api::str2char;
+    use super::tokenizer::Word;
+
+    let env = Environment::new();
+    let mut aliases = Aliases::new();
+    aliases.set("ll", "ls -l").unwrap();
+    aliases.set("ls", "ls -x").unwrap();        // refers to itself
+    aliases.set("a", "b").unwrap();
+    aliases.set("b", "a").unwrap();             // and to each other
+    let words = |line: &str| -> Vec<String> {
+        let tokens = tokenize(&str2char(line), &env).unwrap();
+        aliases.expand(tokens, &env).unwrap().into_iter().map(|token| match token {
+            Token::Word(Word { text, .. }) => text,
+            Token::Operator(operator) => format!("{:?}", operator),
+        }).collect()
+    };
+
+    assert_eq!(words("ll docs | ll > ll"), ["ls", "-l", "docs", "Pipe", "ls", "-l", "Write", "ll"]);
+    assert_eq!(words("'ll' ll"), ["ll", "ll"]);     // quoted or not first, left alone
+    assert_eq!(words("ls"), ["ls", "-x"]);          // expanded once only
+    assert_eq!(words("a"), ["b"]);
+}
\ No newline at end of file
@@ -0,0 +1,89 @@
+use alloc::vec::Vec;
+use alloc::string::String;
+use alloc::format;
+use super::tokenizer::Word;
+
+#[derive(Debug, Clone, Copy)]
+pub struct ArgSpec {               // the arguments a command accepts
+    pub min: usize,
+    pub max: Option<usize>,         // None means no upper limit
+    pub flags: &'static [&'static str],
+    pub usage: &'static str,        // shown after the command name in usage errors, e.g. "<file>..."
+}
+
+impl ArgSpec {
+    pub const fn none() -> ArgSpec {
+        ArgSpec { min: 0, max: Some(0), flags: &[], usage: "" }
+    }
+
+    pub const fn new(min: usize, max: Option<usize>, usage: &'static str) -> ArgSpec {
+        ArgSpec { min, max, flags: &[], usage }
+    }
+
+    pub const fn with_flags(mut self, flags: &'static [&'static str]) -> ArgSpec {
+        self.flags = flags;
+        self
+    }
+
+    pub fn check(&self, words: Vec<Word>) -> Result<Args, ()> {   // split the words after the command into flags and positional arguments
+        let mut args = Args { positional: Vec::new(), flags: Vec::new() };
+        let mut only_positional = false;
+
+        for word in words {
+            if !only_positional && !word.quoted && word.text == "--" {    // everything after '--' is positional
+                only_positional = true;
+            } else if !only_positional && !word.quoted && self.flags.contains(&word.text.as_str()) {
+                args.flags.push(word.text);
+            } else {                    // the other words, '-5' too, are arguments
+                args.positional.push(word.text);
+            }
+        }
+
+        if args.positional.len() < self.min {
+            return Err(());
+        }
+        if let Some(max) = self.max {
+            if args.positional.len() > max {
+                return Err(());
+            }
+        }
+        return Ok(args);
+    }
+
+    pub fn usage(&self, command: &str) -> String {
+        let mut usage = format!("usage: {}", command);
+        for flag in self.flags {
+            usage.push_str(&format!(" [{}]", flag));
+        }
+        if self.usage.len() > 0 {
+            usage.push(' ');
+            usage.push_str(self.usage);
+        }
+        usage
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Args {
+    pub positional: Vec<String>,
+    pub flags: Vec<String>,
+}
+
+impl Args {
+    pub fn has_flag(&self, flag: &str) -> bool {
+        self.flags.iter().any(|f| f == flag)
+    }
+}
+
+#[test_case]
+fn test_check_flags() {
+    let words = |line: &str| line.split(' ').map(|text| Word { text: String::from(text), quoted: false }).collect::<Vec<Word>>();
+    let spec = ArgSpec::new(0, None, "[arg]...").with_flags(&["-n"]);
+
+    let args = spec.check(words("-n -5 -x a")).unwrap();
+    assert_eq!(args.flags, ["-n"]);
+    assert_eq!(args.positional, ["-5", "-x", "a"]);     // undeclared ones are arguments
+    let args = spec.check(words("-- -n")).unwrap();
+    assert_eq!(args.positional, ["-n"]);
+    assert!(ArgSpec::new(1, Some(1), "<name>").check(words("a b")).is_err());
+}
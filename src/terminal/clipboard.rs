@@ -0,0 +1,15 @@
+use alloc::vec::Vec;
+use lazy_static::lazy_static;
+use spin::Mutex;
+
+lazy_static! {
+    static ref CLIPBOARD: Mutex<Vec<char>> = Mutex::new(Vec::new());   // one for all the terminals
+}
+
+pub fn copy(text: Vec<char>) {
+    *CLIPBOARD.lock() = text;
+}
+
+pub fn paste() -> Vec<char> {
+    CLIPBOARD.lock().clone()
+}
\ No newline at end of file
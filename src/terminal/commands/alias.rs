@@ -0,0 +1,57 @@
+use alloc::boxed::Box;
+use core::fmt::Write;
+use crate::println;
+use crate::terminal::args::ArgSpec;
+use super::{Command, CommandFuture, Invocation};
+
+pub struct Alias;
+
+impl Command for Alias {
+    fn name(&self) -> &'static str {
+        "alias"
+    }
+
+    fn help(&self) -> &'static str {
+        "Define a short name for a command, as alias name='cmd args'"
+    }
+
+    fn spec(&self) -> ArgSpec {
+        ArgSpec::new(0, None, "[name[=value]]...")
+    }
+
+    fn run<'a>(&'a self, invocation: Invocation<'a>) -> CommandFuture<'a> {
+        Box::pin(async move {
+            let aliases = &mut invocation.shell.aliases;
+            let stdout = invocation.stdout;
+            if invocation.args.positional.len() == 0 {
+                for (name, value) in aliases.aliases() {
+                    writeln!(stdout, "alias {}='{}'", name, value).unwrap();
+                }
+                return Ok(());
+            }
+
+            let mut result = Ok(());
+            for arg in invocation.args.positional.iter() {
+                match arg.find('=') {
+                    Some(index) => {
+                        let name = &arg[..index];
+                        if aliases.set(name, &arg[index+1..]).is_err() {
+                            println!("Invalid alias name {}", name);
+                            result = Err(());
+                        }
+                    },
+                    None => {
+                        match aliases.get(arg) {
+                            Some(value) => writeln!(stdout, "alias {}='{}'", arg, value).unwrap(),
+                            None => {
+                                println!("No alias called {}", arg);
+                                result = Err(());
+                            }
+                        }
+                    }
+                }
+            }
+            result
+        })
+    }
+}
\ No newline at end of file
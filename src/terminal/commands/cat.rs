@@ -0,0 +1,43 @@
+use alloc::boxed::Box;
+use core::fmt::Write;
+use crate::api::str2char;
+use crate::terminal::args::ArgSpec;
+use crate::terminal::stream::lines2text;
+use super::{Command, CommandFuture, Invocation};
+
+pub struct Cat;
+
+impl Command for Cat {
+    fn name(&self) -> &'static str {
+        "cat"
+    }
+
+    fn help(&self) -> &'static str {
+        "Print documents, or the input of a pipe without arguments"
+    }
+
+    fn spec(&self) -> ArgSpec {
+        ArgSpec::new(0, None, "[file]...")
+    }
+
+    fn run<'a>(&'a self, invocation: Invocation<'a>) -> CommandFuture<'a> {
+        Box::pin(async move {
+            let stdout = invocation.stdout;
+            if invocation.args.positional.len() == 0 {
+                if let Some(text) = invocation.stdin {
+                    write!(stdout, "{}", text).unwrap();
+                }
+                return Ok(());
+            }
+
+            let mut result = Ok(());
+            for name in invocation.args.positional.iter() {
+                match invocation.shell.file_system.read_file(str2char(name), false) {
+                    Ok(content) => write!(stdout, "{}", lines2text(&content)).unwrap(),
+                    Err(_) => result = Err(()),
+                }
+            }
+            result
+        })
+    }
+}
\ No newline at end of file
@@ -0,0 +1,34 @@
+use alloc::boxed::Box;
+use alloc::vec;
+use crate::api::{str2char, char_vec_cmp};
+use crate::terminal::args::ArgSpec;
+use super::{Command, CommandFuture, Invocation};
+
+pub struct Cd;
+
+impl Command for Cd {
+    fn name(&self) -> &'static str {
+        "cd"
+    }
+
+    fn help(&self) -> &'static str {
+        "Enter a folder, '..' goes back to the parent"
+    }
+
+    fn spec(&self) -> ArgSpec {
+        ArgSpec::new(1, Some(1), "<folder>")
+    }
+
+    fn run<'a>(&'a self, invocation: Invocation<'a>) -> CommandFuture<'a> {
+        Box::pin(async move {
+            let file_system = &mut invocation.shell.file_system;
+            let folder = str2char(&invocation.args.positional[0]);
+            if char_vec_cmp(&folder, &vec!['.', '.']) {
+                file_system.outof_forlder();
+            } else {
+                file_system.into_folder(folder);
+            }
+            Ok(())
+        })
+    }
+}
\ No newline at end of file
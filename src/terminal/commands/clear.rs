@@ -0,0 +1,27 @@
+use alloc::boxed::Box;
+use crate::print;
+use crate::terminal::args::ArgSpec;
+use super::{Command, CommandFuture, Invocation};
+
+pub struct Clear;
+
+impl Command for Clear {
+    fn name(&self) -> &'static str {
+        "clear"
+    }
+
+    fn help(&self) -> &'static str {
+        "Clear the screen"
+    }
+
+    fn spec(&self) -> ArgSpec {
+        ArgSpec::none()
+    }
+
+    fn run<'a>(&'a self, _invocation: Invocation<'a>) -> CommandFuture<'a> {
+        Box::pin(async move {
+            print!("\x1b[2J\x1b[H");          // erase the screen, the cursor to the top left
+            Ok(())
+        })
+    }
+}
\ No newline at end of file
@@ -0,0 +1,39 @@
+use alloc::boxed::Box;
+use core::fmt::Write;
+use crate::terminal::args::ArgSpec;
+use super::{Command, CommandFuture, Invocation};
+
+pub struct Echo;
+
+impl Command for Echo {
+    fn name(&self) -> &'static str {
+        "echo"
+    }
+
+    fn help(&self) -> &'static str {
+        "Print the arguments, '-n' leaves out the newline"
+    }
+
+    fn spec(&self) -> ArgSpec {
+        ArgSpec::new(0, None, "<text>...").with_flags(&["-n"])
+    }
+
+    fn run<'a>(&'a self, invocation: Invocation<'a>) -> CommandFuture<'a> {
+        Box::pin(async move {
+            let args = invocation.args;
+            let stdout = invocation.stdout;
+            let len = args.positional.len();
+            for (i, word) in args.positional.iter().enumerate() {
+                if i < len - 1 {
+                    write!(stdout, "{} ", word).unwrap();
+                } else {
+                    write!(stdout, "{}", word).unwrap();
+                }
+            }
+            if !args.has_flag("-n") {
+                writeln!(stdout, "").unwrap();
+            }
+            Ok(())
+        })
+    }
+}
\ No newline at end of file
@@ -0,0 +1,81 @@
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use crate::println;
+use futures_util::stream::StreamExt;
+use crate::api::str2char;
+use crate::editor::{Editor, Action};
+use crate::buffer::split;
+use crate::buffer::kbd::Decoder;
+use crate::terminal::args::ArgSpec;
+use crate::terminal::session::{self, STATES};
+use super::{Command, CommandFuture, Invocation};
+
+pub struct Edit;
+
+impl Command for Edit {
+    fn name(&self) -> &'static str {
+        "edit"
+    }
+
+    fn help(&self) -> &'static str {
+        "Edit a document full-screen, Ctrl+S saves and Ctrl+Q quits"
+    }
+
+    fn spec(&self) -> ArgSpec {
+        ArgSpec::new(1, Some(1), "<file>")
+    }
+
+    fn run<'a>(&'a self, invocation: Invocation<'a>) -> CommandFuture<'a> {
+        Box::pin(async move {
+            let shell = invocation.shell;
+            let name = invocation.args.positional[0].clone();
+            let content = match shell.file_system.read_file(str2char(&name), false) {
+                Ok(content) => content,
+                Err(_) => return Err(()),
+            };
+
+            let id = shell.id;
+            if id == session::SERIAL || split::is_split() {
+                println!("edit needs a whole VGA screen");
+                return Err(());
+            }
+            let folder: Vec<Vec<char>> = shell.file_system.get_folder().split('\\').map(|name| name.chars().collect()).collect();
+            let mut editor = Editor::new(&name, &content);
+            if shell.env.path().contains(&folder) {    // the programs 'run' finds are highlighted
+                editor = editor.with_highlighting();
+            }
+            let mut scancodes = session::scancodes(id);
+            let mut keyboard = Decoder::new();
+
+            unsafe {
+                STATES[id].editing = true;      // the keys come here instead of the shell
+            }
+            editor.render(id);
+            while let Some(scancode) = scancodes.next().await {
+                if let Some(press) = keyboard.add_byte(scancode) {
+                    match editor.handle(press) {
+                        Action::Save => {
+                            match shell.file_system.edit_file(str2char(&name), editor.lines()) {
+                                Ok(_) => editor.saved(),
+                                Err(_) => editor.tell("The document could not be saved"),
+                            }
+                        },
+                        Action::Quit => break,
+                        Action::None => {}
+                    }
+                    editor.render(id);
+                }
+            }
+            unsafe {
+                STATES[id].editing = false;
+            }
+
+            editor.close(id);
+            Ok(())
+        })
+    }
+
+    fn changes_files(&self) -> bool {
+        true
+    }
+}
\ No newline at end of file
@@ -0,0 +1,29 @@
+use alloc::boxed::Box;
+use core::fmt::Write;
+use crate::terminal::args::ArgSpec;
+use super::{Command, CommandFuture, Invocation};
+
+pub struct Env;
+
+impl Command for Env {
+    fn name(&self) -> &'static str {
+        "env"
+    }
+
+    fn help(&self) -> &'static str {
+        "List the variables"
+    }
+
+    fn spec(&self) -> ArgSpec {
+        ArgSpec::none()
+    }
+
+    fn run<'a>(&'a self, invocation: Invocation<'a>) -> CommandFuture<'a> {
+        Box::pin(async move {
+            for (name, value) in invocation.shell.env.vars() {
+                writeln!(invocation.stdout, "{}={}", name, value).unwrap();
+            }
+            Ok(())
+        })
+    }
+}
\ No newline at end of file
@@ -0,0 +1,134 @@
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use crate::println;
+use crate::api::{str2char, lines2bytes};
+use crate::buffer::kbd::Decoder;
+use crate::file::FileType;
+use crate::process::{self, elf, programs, syscall, Loaded, Program, Request};
+use crate::timer::sleep;
+use crate::terminal::args::ArgSpec;
+use crate::terminal::session::{self, STATES};
+use crate::terminal::shell::Shell;
+use pc_keyboard::DecodedKey;
+use super::{Command, CommandFuture, Invocation};
+
+pub struct Exec;
+
+impl Command for Exec {
+    fn name(&self) -> &'static str {
+        "exec"
+    }
+
+    fn help(&self) -> &'static str {
+        "Run a program in ring 3, built-in or an ELF executable in PATH; Ctrl+D ends its input and Ctrl+C stops it"
+    }
+
+    fn spec(&self) -> ArgSpec {
+        ArgSpec::new(1, None, "<program> [arg]...")
+    }
+
+    fn run<'a>(&'a self, invocation: Invocation<'a>) -> CommandFuture<'a> {
+        Box::pin(async move {
+            let shell = invocation.shell;
+            let id = shell.id;
+            let args = invocation.args.positional;
+            let name = args[0].clone();
+            let loaded = load(shell, &args)?;
+            let group = process::start(loaded, id, None).map_err(|error| println!("{}: {}", name, error))?;
+
+            unsafe {
+                STATES[id].editing = true;      // the keys come here, for the process to read
+            }
+            let mut keyboard = Decoder::new();
+            while !process::finished(group) {
+                serve(shell, group);
+                while let Some(byte) = session::try_key(id) {
+                    let c = if id == session::SERIAL {
+                        Some(if byte == b'\r' { '\n' } else { byte as char })
+                    } else {
+                        match keyboard.add_byte(byte).map(|press| press.key) {
+                            Some(DecodedKey::Unicode(c)) => Some(c),
+                            _ => None,
+                        }
+                    };
+                    match c {
+                        Some('\u{3}') => process::kill(group),
+                        Some('\u{4}') => process::end_input(group),
+                        Some(c) => {
+                            let mut bytes = [0; 4];
+                            process::give_input(group, c.encode_utf8(&mut bytes).as_bytes());
+                        },
+                        None => {}
+                    }
+                }
+                if shell.cancelled() {          // Ctrl+C on the serial line, or 'kill'
+                    process::kill(group);
+                }
+                sleep::sleep_ticks(1).await;
+            }
+            unsafe {
+                STATES[id].editing = false;
+            }
+
+            match process::reap(group) {
+                Some(0) => Ok(()),
+                Some(status) => {
+                    println!("{} exited with {}", name, status);
+                    Err(())
+                },
+                None => Err(()),            // stopped, or a fault which was told already
+            }
+        })
+    }
+}
+
+/* a built-in program or an executable of the current folder or PATH, with its arguments and the environment */
+fn load(shell: &mut Shell, args: &[String]) -> Result<Loaded, ()> {
+    let name = &args[0];
+    let env: Vec<String> = shell.env.vars().iter().map(|(name, value)| format!("{}={}", name, value)).collect();
+    if let Some(image) = programs::find(name) {
+        return process::load(&Program::flat(image), args, &env).map_err(|error| println!("{}: {}", name, error));
+    }
+    let document;
+    let bytes = match programs::find_executable(name) {
+        Some(bytes) => bytes,
+        None => {
+            let content = shell.find_script(name)?;
+            document = lines2bytes(&content).filter(|bytes| elf::is_elf(bytes)).ok_or_else(|| {
+                println!("{} is not a program, the built-in ones are {}, {}", name, programs::NAMES.join(", "), programs::EXECUTABLES.join(", "));
+            })?;
+            &document[..]
+        }
+    };
+    let program = elf::parse(bytes).map_err(|error| println!("{}: {}", name, error))?;
+    process::load(&program, args, &env).map_err(|error| println!("{}: {}", name, error))
+}
+
+/* what the processes ask of the shell: its documents and the programs */
+fn serve(shell: &mut Shell, group: usize) {
+    while let Some((pid, request)) = process::take_request(group) {
+        let reply = match request {
+            Request::Open(path) => {
+                let path = str2char(&path);
+                if shell.file_system.names().iter().any(|(n, t)| *n == path && *t == FileType::Document) {
+                    match shell.file_system.read_file(path, false) {
+                        Ok(lines) => {
+                            let lines: Vec<String> = lines.iter().map(|line| line.iter().collect()).collect();
+                            process::add_file(pid, lines.join("\n").into_bytes())
+                        },
+                        Err(_) => Err(syscall::ENOENT),
+                    }
+                } else {
+                    Err(syscall::ENOENT)
+                }
+            },
+            Request::Spawn(name) => match load(shell, &[name]) {
+                Ok(loaded) => process::start(loaded, shell.id, Some(group)).map_err(|_| syscall::EAGAIN),
+                Err(_) => Err(syscall::ENOENT),
+            },
+        };
+        process::reply(pid, reply);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,62 @@
+use alloc::boxed::Box;
+use core::fmt::Write;
+use crate::println;
+use crate::api::{char2int, str2char};
+use crate::timer::sleep;
+use crate::terminal::args::ArgSpec;
+use crate::terminal::jobs::{self, JobState};
+use super::{Command, CommandFuture, Invocation};
+
+pub struct Fg;
+
+impl Command for Fg {
+    fn name(&self) -> &'static str {
+        "fg"
+    }
+
+    fn help(&self) -> &'static str {
+        "Wait for a job, the latest one by default, Ctrl+C interrupts it"
+    }
+
+    fn spec(&self) -> ArgSpec {
+        ArgSpec::new(0, Some(1), "[job]")
+    }
+
+    fn run<'a>(&'a self, invocation: Invocation<'a>) -> CommandFuture<'a> {
+        Box::pin(async move {
+            let id = invocation.shell.id;
+            let running = jobs::list(id).into_iter()
+                .filter(|j| j.background && j.state == JobState::Running);
+            let job = match invocation.args.positional.get(0) {
+                Some(number) => match char2int(str2char(number)) {
+                    Ok(number) => running.filter(|j| j.id == number as usize).next(),
+                    Err(_) => {
+                        println!("Job must be a number");
+                        return Err(());
+                    }
+                },
+                None => running.last(),
+            };
+            let job = match job {
+                Some(job) => job,
+                None => {
+                    println!("No such job");
+                    return Err(());
+                }
+            };
+
+            jobs::wait(job.id).unwrap();
+            writeln!(invocation.stdout, "{}", job.line).unwrap();
+            loop {
+                match jobs::get(job.id) {
+                    Some(waited) if waited.state == JobState::Running => sleep::sleep_ticks(1).await,
+                    Some(waited) => {
+                        jobs::forget(job.id);
+                        return if waited.state == JobState::Done(0) { Ok(()) } else { Err(()) };
+                    },
+                    None => return Ok(()),
+                }
+            }
+        })
+    }
+}
\ No newline at end of file
@@ -0,0 +1,27 @@
+use alloc::boxed::Box;
+use core::fmt::Write;
+use crate::terminal::args::ArgSpec;
+use super::{Command, CommandFuture, Invocation};
+
+pub struct Hello;
+
+impl Command for Hello {
+    fn name(&self) -> &'static str {
+        "hello"
+    }
+
+    fn help(&self) -> &'static str {
+        "Say hello"
+    }
+
+    fn spec(&self) -> ArgSpec {
+        ArgSpec::none()
+    }
+
+    fn run<'a>(&'a self, invocation: Invocation<'a>) -> CommandFuture<'a> {
+        Box::pin(async move {
+            writeln!(invocation.stdout, "Hello, world!").unwrap();
+            Ok(())
+        })
+    }
+}
\ No newline at end of file
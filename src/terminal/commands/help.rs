@@ -0,0 +1,48 @@
+use alloc::boxed::Box;
+use core::fmt::Write;
+use crate::println;
+use crate::terminal::args::ArgSpec;
+use super::{Command, CommandFuture, Invocation, REGISTRY};
+
+pub struct Help;
+
+impl Command for Help {
+    fn name(&self) -> &'static str {
+        "help"
+    }
+
+    fn help(&self) -> &'static str {
+        "List the commands, or show how to use one"
+    }
+
+    fn spec(&self) -> ArgSpec {
+        ArgSpec::new(0, Some(1), "[command]")
+    }
+
+    fn run<'a>(&'a self, invocation: Invocation<'a>) -> CommandFuture<'a> {
+        Box::pin(async move {
+            let stdout = invocation.stdout;
+            match invocation.args.positional.first() {
+                Some(name) => {
+                    match REGISTRY.find(name) {
+                        Some(command) => {
+                            writeln!(stdout, "{}", command.spec().usage(command.name())).unwrap();
+                            writeln!(stdout, "{}", command.help()).unwrap();
+                            Ok(())
+                        },
+                        None => {
+                            println!("No command called {}", name);
+                            Err(())
+                        }
+                    }
+                },
+                None => {
+                    for command in REGISTRY.commands() {
+                        writeln!(stdout, "{:<10}{}", command.name(), command.help()).unwrap();
+                    }
+                    Ok(())
+                }
+            }
+        })
+    }
+}
\ No newline at end of file
@@ -0,0 +1,26 @@
+use alloc::boxed::Box;
+use crate::terminal::args::ArgSpec;
+use super::{Command, CommandFuture, Invocation};
+
+pub struct History;
+
+impl Command for History {
+    fn name(&self) -> &'static str {
+        "history"
+    }
+
+    fn help(&self) -> &'static str {
+        "List the previous commands, '!n' runs the n-th again"
+    }
+
+    fn spec(&self) -> ArgSpec {
+        ArgSpec::none()
+    }
+
+    fn run<'a>(&'a self, invocation: Invocation<'a>) -> CommandFuture<'a> {
+        Box::pin(async move {
+            invocation.shell.controller.history.print(invocation.stdout);
+            Ok(())
+        })
+    }
+}
\ No newline at end of file
@@ -0,0 +1,38 @@
+use alloc::boxed::Box;
+use core::fmt::Write;
+use crate::terminal::args::ArgSpec;
+use crate::terminal::jobs::{self, JobState};
+use super::{Command, CommandFuture, Invocation};
+
+pub struct Jobs;
+
+impl Command for Jobs {
+    fn name(&self) -> &'static str {
+        "jobs"
+    }
+
+    fn help(&self) -> &'static str {
+        "List the jobs started with '&' in this terminal"
+    }
+
+    fn spec(&self) -> ArgSpec {
+        ArgSpec::none()
+    }
+
+    fn run<'a>(&'a self, invocation: Invocation<'a>) -> CommandFuture<'a> {
+        Box::pin(async move {
+            for job in jobs::list(invocation.shell.id) {
+                if !job.background {    // the line running 'jobs' itself
+                    continue;
+                }
+                let state = match job.state {
+                    JobState::Running => "Running",
+                    JobState::Done(_) => "Done",
+                    JobState::Killed => "Killed",
+                };
+                writeln!(invocation.stdout, "[{}] {}  {}", job.id, state, job.line).unwrap();
+            }
+            Ok(())
+        })
+    }
+}
\ No newline at end of file
@@ -0,0 +1,66 @@
+use alloc::boxed::Box;
+use crate::println;
+use crate::buffer::kbd::{self, Layout};
+use crate::terminal::args::ArgSpec;
+use super::{Command, CommandFuture, Invocation};
+
+pub struct Kbd;
+
+impl Command for Kbd {
+    fn name(&self) -> &'static str {
+        "kbd"
+    }
+
+    fn help(&self) -> &'static str {
+        "Show the keyboard settings, choose a layout or set how held keys repeat"
+    }
+
+    fn spec(&self) -> ArgSpec {
+        ArgSpec::new(0, Some(3), "[us|uk|dvorak|azerty|de] | [repeat <delay-ms> <rate>]")
+    }
+
+    fn run<'a>(&'a self, invocation: Invocation<'a>) -> CommandFuture<'a> {
+        Box::pin(async move {
+            let args = &invocation.args.positional;
+            match args.len() {
+                0 => {
+                    let (caps, num, scroll) = kbd::locks();
+                    let (delay, rate) = kbd::repeat();
+                    let on = |lock: bool| if lock { "on" } else { "off" };
+                    println!("layout: {}", kbd::layout().name());
+                    println!("caps lock: {}, num lock: {}, scroll lock: {}", on(caps), on(num), on(scroll));
+                    println!("repeat: after {} ms, {} a second", delay, rate);
+                    Ok(())
+                },
+                1 => {
+                    match Layout::from_name(&args[0]) {
+                        Some(layout) => {
+                            kbd::set_layout(layout);
+                            Ok(())
+                        },
+                        None => {
+                            println!("No layout {}, try us, uk, dvorak, azerty or de", args[0]);
+                            Err(())
+                        }
+                    }
+                },
+                _ => {
+                    if args[0] != "repeat" || args.len() != 3 {
+                        println!("Usage: kbd repeat <delay-ms> <rate>");
+                        return Err(());
+                    }
+                    let (delay, rate) = match (args[1].parse::<usize>(), args[2].parse::<usize>()) {
+                        (Ok(delay), Ok(rate)) => (delay, rate),
+                        _ => {
+                            println!("The delay and the rate are numbers");
+                            return Err(());
+                        }
+                    };
+                    kbd::set_repeat(delay, rate).map_err(|_| {
+                        println!("The delay goes from 250 to 1000 ms and the rate from 2 to 30 a second");
+                    })
+                }
+            }
+        })
+    }
+}
\ No newline at end of file
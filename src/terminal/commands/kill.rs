@@ -0,0 +1,42 @@
+use alloc::boxed::Box;
+use crate::println;
+use crate::api::{char2int, str2char};
+use crate::terminal::args::ArgSpec;
+use crate::terminal::jobs;
+use super::{Command, CommandFuture, Invocation};
+
+pub struct Kill;
+
+impl Command for Kill {
+    fn name(&self) -> &'static str {
+        "kill"
+    }
+
+    fn help(&self) -> &'static str {
+        "Ask jobs to stop, as Ctrl+C would"
+    }
+
+    fn spec(&self) -> ArgSpec {
+        ArgSpec::new(1, None, "<job>...")
+    }
+
+    fn run<'a>(&'a self, invocation: Invocation<'a>) -> CommandFuture<'a> {
+        Box::pin(async move {
+            let mut result = Ok(());
+            for number in invocation.args.positional.iter() {
+                let found = match char2int(str2char(number)) {
+                    Ok(id) => match jobs::get(id as usize) {
+                        Some(job) if job.session == invocation.shell.id => jobs::cancel(job.id).is_ok(),
+                        _ => false,
+                    },
+                    Err(_) => false,
+                };
+                if !found {
+                    println!("No running job {}", number);
+                    result = Err(());
+                }
+            }
+            result
+        })
+    }
+}
\ No newline at end of file
@@ -0,0 +1,38 @@
+use alloc::boxed::Box;
+use alloc::string::String;
+use core::fmt::Write;
+use crate::terminal::args::ArgSpec;
+use super::{Command, CommandFuture, Invocation};
+
+pub struct Ls;
+
+impl Command for Ls {
+    fn name(&self) -> &'static str {
+        "ls"
+    }
+
+    fn help(&self) -> &'static str {
+        "List the files in the current folder"
+    }
+
+    fn spec(&self) -> ArgSpec {
+        ArgSpec::none()
+    }
+
+    fn run<'a>(&'a self, invocation: Invocation<'a>) -> CommandFuture<'a> {
+        Box::pin(async move {
+            let stdout = invocation.stdout;
+            let names = invocation.shell.file_system.names();
+            let separator = if stdout.is_console() { "  " } else { "\n" };    // one name per line for the next command of a pipe
+            for (i, (name, _)) in names.iter().enumerate() {
+                let name: String = name.iter().collect();
+                if i < names.len() - 1 {
+                    write!(stdout, "{}{}", name, separator).unwrap();
+                } else {
+                    writeln!(stdout, "{}", name).unwrap();
+                }
+            }
+            Ok(())
+        })
+    }
+}
\ No newline at end of file
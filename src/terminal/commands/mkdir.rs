@@ -0,0 +1,47 @@
+use alloc::boxed::Box;
+use crate::println;
+use crate::api::str2char;
+use crate::file::{FileNode, FileType};
+use crate::terminal::args::ArgSpec;
+use super::{Command, CommandFuture, Invocation};
+
+pub struct Mkdir;
+
+impl Command for Mkdir {
+    fn name(&self) -> &'static str {
+        "mkdir"
+    }
+
+    fn help(&self) -> &'static str {
+        "Create folders"
+    }
+
+    fn spec(&self) -> ArgSpec {
+        ArgSpec::new(1, None, "<folder>...")
+    }
+
+    fn run<'a>(&'a self, invocation: Invocation<'a>) -> CommandFuture<'a> {
+        Box::pin(async move {
+            let file_system = &mut invocation.shell.file_system;
+            let mut result = Ok(());
+            for name in &invocation.args.positional {
+                let para = str2char(name);
+                match file_system.retrieve(para.clone()) {
+                    Ok((_, _)) => {
+                        println!("The file exsits");
+                        result = Err(());
+                    },
+                    Err(_) => {
+                        let file = FileNode::new(para, FileType::Folder);
+                        file_system.add_file(file).unwrap();
+                    }
+                }
+            }
+            result
+        })
+    }
+
+    fn changes_files(&self) -> bool {
+        true
+    }
+}
\ No newline at end of file
@@ -0,0 +1,210 @@
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use alloc::string::String;
+use core::future::Future;
+use core::pin::Pin;
+use lazy_static::lazy_static;
+use super::args::{ArgSpec, Args};
+use super::tokenizer::{Word, TokenizeError};
+use super::pipeline::PipelineError;
+use super::stream::Output;
+use super::shell::Shell;
+
+pub mod hello;
+pub mod echo;
+pub mod clear;
+pub mod sleep;
+pub mod cd;
+pub mod ls;
+pub mod edit;
+pub mod mk;
+pub mod mkdir;
+pub mod rm;
+pub mod run;
+pub mod history;
+pub mod help;
+pub mod cat;
+pub mod set;
+pub mod unset;
+pub mod env;
+pub mod sh;
+pub mod alias;
+pub mod unalias;
+pub mod jobs;
+pub mod fg;
+pub mod kill;
+pub mod split;
+pub mod kbd;
+pub mod exec;
+
+pub type CommandFuture<'a> = Pin<Box<dyn Future<Output = Result<(), ()>> + 'a>>;
+
+pub struct Invocation<'a> {         // everything a command can touch while running
+    pub shell: &'a mut Shell,
+    pub args: Args,
+    pub stdin: Option<String>,      // what the previous command of a pipe or a '<' file produced
+    pub stdout: &'a mut Output,
+}
+
+/*
+A shell command. Adding a command means writing a module implementing this trait
+and registering it in Registry::init.
+*/
+pub trait Command: Send + Sync {
+    fn name(&self) -> &'static str;
+    fn help(&self) -> &'static str;     // one line shown by 'help'
+    fn spec(&self) -> ArgSpec;          // the arguments are checked against it before 'run' is called
+    fn run<'a>(&'a self, invocation: Invocation<'a>) -> CommandFuture<'a>;
+    fn changes_files(&self) -> bool {   // refused in a job of '&', which works on a copy of the documents
+        false
+    }
+}
+
+pub enum CommandError {
+    Syntax(TokenizeError),
+    Pipeline(PipelineError),
+    NotFound,
+    Usage(String),
+    Background,                     // a line of '&' would change the documents
+}
+
+impl CommandError {
+    pub fn message(&self) -> String {
+        match self {
+            CommandError::Syntax(e) => String::from(e.message()),
+            CommandError::Pipeline(e) => String::from(e.message()),
+            CommandError::NotFound => String::from("Invalid command"),
+            CommandError::Usage(usage) => usage.clone(),
+            CommandError::Background => String::from("A job in the background can't change the documents, run it without '&'"),
+        }
+    }
+
+    pub fn status(&self) -> u8 {    // the '$?' of a line that could not be run
+        match self {
+            CommandError::NotFound => 127,
+            _ => 2,
+        }
+    }
+}
+
+pub struct Registry {
+    commands: Vec<Box<dyn Command>>,
+}
+
+impl Registry {
+    pub fn new() -> Registry {
+        Registry {
+            commands: Vec::new(),
+        }
+    }
+
+    pub fn init(&mut self) {
+        self.register(Box::new(hello::Hello));
+        self.register(Box::new(echo::Echo));
+        self.register(Box::new(sleep::Sleep));
+
+        self.register(Box::new(cd::Cd));
+        self.register(Box::new(ls::Ls));
+        self.register(Box::new(clear::Clear));
+        self.register(Box::new(split::Split));
+        self.register(Box::new(kbd::Kbd));
+        self.register(Box::new(edit::Edit));
+        self.register(Box::new(mk::Mk));
+        self.register(Box::new(mkdir::Mkdir));
+        self.register(Box::new(rm::Rm));
+        self.register(Box::new(cat::Cat));
+
+        self.register(Box::new(run::Run));
+        self.register(Box::new(exec::Exec));
+        self.register(Box::new(sh::Sh));
+        self.register(Box::new(jobs::Jobs));
+        self.register(Box::new(fg::Fg));
+        self.register(Box::new(kill::Kill));
+
+        self.register(Box::new(set::Set));
+        self.register(Box::new(unset::Unset));
+        self.register(Box::new(env::Env));
+        self.register(Box::new(alias::Alias));
+        self.register(Box::new(unalias::Unalias));
+
+        self.register(Box::new(history::History));
+        self.register(Box::new(help::Help));
+    }
+
+    pub fn register(&mut self, command: Box<dyn Command>) {
+        if self.find(command.name()).is_some() {
+            panic!("command '{}' registered twice", command.name());
+        }
+        self.commands.push(command);
+    }
+
+    pub fn find(&self, name: &str) -> Option<&dyn Command> {
+        self.commands.iter().find(|c| c.name() == name).map(|c| c.as_ref())
+    }
+
+    pub fn commands(&self) -> &Vec<Box<dyn Command>> {
+        &self.commands
+    }
+
+    pub fn resolve(&self, mut words: Vec<Word>) -> Result<(&dyn Command, Args), CommandError> {  // a known command and its checked arguments
+        if words.len() == 0 {
+            return Err(CommandError::NotFound);
+        }
+        let name = words.remove(0);
+
+        match self.find(&name.text) {
+            Some(command) => {
+                match command.spec().check(words) {
+                    Ok(args) => Ok((command, args)),
+                    Err(_) => Err(CommandError::Usage(command.spec().usage(command.name()))),
+                }
+            },
+            None => Err(CommandError::NotFound),
+        }
+    }
+}
+
+lazy_static! {
+    pub static ref REGISTRY: Registry = {
+        let mut registry = Registry::new();
+        registry.init();
+        registry
+    };
+}
+
+#[test_case]
+fn test_registry_resolve() {
+    let words = |line: &str| line.split(' ').map(|text| Word { text: String::from(text), quoted: false }).collect::<Vec<Word>>();
+
+    match REGISTRY.resolve(words("help cd")) {
+        Ok((command, args)) => {
+            assert_eq!(command.name(), "help");
+            assert_eq!(args.positional, ["cd"]);
+        },
+        Err(_) => panic!("help not found"),
+    }
+    match REGISTRY.resolve(words("help cd ls")) {
+        Err(error) => assert_eq!(error.message(), "usage: help [command]"),
+        Ok(_) => panic!("too many arguments accepted"),
+    }
+    match REGISTRY.resolve(words("nothing")) {
+        Err(error) => assert_eq!(error.status(), 127),
+        Ok(_) => panic!("unknown command resolved"),
+    }
+}
+
+#[test_case]
+fn test_help_output() {
+    use futures_util::FutureExt;
+
+    let mut shell = Shell::new(0);
+    let mut stdout = Output::Capture(String::new());
+    let args = Args { positional: Vec::new(), flags: Vec::new() };
+    let result = REGISTRY.find("help").unwrap().run(Invocation { shell: &mut shell, args, stdin: None, stdout: &mut stdout }).now_or_never();
+    assert_eq!(result, Some(Ok(())));
+    let text = stdout.take().unwrap();
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines.len(), REGISTRY.commands().len());            // one line per command, in the order of 'init'
+    assert!(lines[0].starts_with("hello     "));
+    assert!(lines[lines.len() - 1].starts_with("help      List the commands"));
+}
\ No newline at end of file
@@ -0,0 +1,49 @@
+use alloc::boxed::Box;
+use crate::{print, println};
+use crate::api::str2char;
+use crate::terminal::args::ArgSpec;
+use super::{Command, CommandFuture, Invocation};
+
+pub struct Rm;
+
+impl Command for Rm {
+    fn name(&self) -> &'static str {
+        "rm"
+    }
+
+    fn help(&self) -> &'static str {
+        "Remove files or folders"
+    }
+
+    fn spec(&self) -> ArgSpec {
+        ArgSpec::new(1, None, "<file>...")
+    }
+
+    fn run<'a>(&'a self, invocation: Invocation<'a>) -> CommandFuture<'a> {
+        Box::pin(async move {
+            let file_system = &mut invocation.shell.file_system;
+            let mut result = Ok(());
+            for name in &invocation.args.positional {
+                let para = str2char(name);
+                match file_system.retrieve(para.clone()) {
+                    Ok((file, _)) => {
+                        file_system.remove_file(file).unwrap();
+                    },
+                    Err(_) => {
+                        print!("No file called ");
+                        for c in para {
+                            print!("{}", c);
+                        }
+                        println!("");
+                        result = Err(());
+                    }
+                }
+            }
+            result
+        })
+    }
+
+    fn changes_files(&self) -> bool {
+        true
+    }
+}
\ No newline at end of file
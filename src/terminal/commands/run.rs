@@ -0,0 +1,49 @@
+use alloc::boxed::Box;
+use crate::println;
+use crate::compiler::compile;
+use crate::task::thread;
+use crate::timer::sleep;
+use crate::terminal::args::ArgSpec;
+use super::{Command, CommandFuture, Invocation};
+
+pub struct Run;
+
+impl Command for Run {
+    fn name(&self) -> &'static str {
+        "run"
+    }
+
+    fn help(&self) -> &'static str {
+        "Compile and run a script, found in the current folder or in PATH"
+    }
+
+    fn spec(&self) -> ArgSpec {
+        ArgSpec::new(1, Some(1), "<file>")
+    }
+
+    fn run<'a>(&'a self, invocation: Invocation<'a>) -> CommandFuture<'a> {
+        Box::pin(async move {
+            let shell = invocation.shell;
+            let content = shell.find_script(&invocation.args.positional[0])?;
+            /* in a thread of its own, so that a script which never ends doesn't stop the terminals */
+            let id = shell.id;
+            let script = match thread::spawn_thread(Some(id), move || compile::compile_run(content, id)) {
+                Ok(script) => script,
+                Err(_) => {
+                    println!("Too many threads running, try again later");
+                    return Err(());
+                }
+            };
+            while !script.is_finished() {
+                if shell.cancelled() {      // the script stops at its next statement
+                    script.cancel();
+                }
+                sleep::sleep_ticks(1).await;
+            }
+            if shell.cancelled() {
+                return Err(());
+            }
+            Ok(())
+        })
+    }
+}
\ No newline at end of file
@@ -0,0 +1,39 @@
+use alloc::boxed::Box;
+use alloc::string::String;
+use crate::println;
+use crate::terminal::args::ArgSpec;
+use super::{Command, CommandFuture, Invocation};
+
+pub struct Set;
+
+impl Command for Set {
+    fn name(&self) -> &'static str {
+        "set"
+    }
+
+    fn help(&self) -> &'static str {
+        "Set a variable, as 'set NAME value' or 'set NAME=value'"
+    }
+
+    fn spec(&self) -> ArgSpec {
+        ArgSpec::new(1, None, "<name> [value]...")
+    }
+
+    fn run<'a>(&'a self, invocation: Invocation<'a>) -> CommandFuture<'a> {
+        Box::pin(async move {
+            let args = invocation.args.positional;
+            let (name, value) = match args[0].find('=') {
+                Some(index) => {
+                    let line = args.join(" ");
+                    (String::from(&line[..index]), String::from(&line[index+1..]))
+                },
+                None => (args[0].clone(), args[1..].join(" ")),
+            };
+            if invocation.shell.env.set(&name, &value).is_err() {
+                println!("Invalid variable name {}", name);
+                return Err(());
+            }
+            Ok(())
+        })
+    }
+}
\ No newline at end of file
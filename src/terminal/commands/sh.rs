@@ -0,0 +1,98 @@
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use alloc::string::String;
+use alloc::format;
+use crate::terminal::args::ArgSpec;
+use crate::terminal::shell::Shell;
+use crate::terminal::stream::Output;
+use super::{Command, CommandFuture, Invocation};
+
+pub struct Sh;
+
+impl Command for Sh {
+    fn name(&self) -> &'static str {
+        "sh"
+    }
+
+    fn help(&self) -> &'static str {
+        "Run a document line by line as commands, '-e' stops at the first error"
+    }
+
+    fn spec(&self) -> ArgSpec {
+        ArgSpec::new(1, None, "<file> [arg]...").with_flags(&["-e"])
+    }
+
+    fn run<'a>(&'a self, invocation: Invocation<'a>) -> CommandFuture<'a> {
+        Box::pin(async move {
+            let shell = invocation.shell;
+            let args = invocation.args;
+            let lines = match shell.find_script(&args.positional[0]) {
+                Ok(lines) => lines,
+                Err(_) => return Err(()),
+            };
+            run_script(shell, lines, &args.positional, args.has_flag("-e"), invocation.stdout).await
+        })
+    }
+}
+
+/*
+Run every line of a script as if it was typed, skipping empty lines and '#' comments.
+'args' become $0..$n while it runs, $0 being the script itself.
+*/
+pub async fn run_script(shell: &mut Shell, lines: Vec<Vec<char>>, args: &[String], exit_on_error: bool, output: &mut Output) -> Result<(), ()> {
+    let saved = shell.env.set_args(args);
+    let mut result = Ok(());
+
+    for (number, line) in lines.iter().enumerate() {
+        let text: String = line.iter().collect();
+        let text = text.trim();
+        if text.len() == 0 || text.starts_with('#') {
+            continue;
+        }
+        if shell.cancelled() {          // Ctrl+C or 'kill', give up the rest of the script
+            result = Err(());
+            break;
+        }
+
+        let status = match shell.execute(line, &mut *output).await {
+            Ok(_) => 0,
+            Err(None) => 1,
+            Err(Some(error)) => {
+                shell.queue("println", format!("{}:{}: {}", args[0], number + 1, error.message()));
+                error.status()
+            }
+        };
+        shell.env.status = status;
+        result = if status == 0 { Ok(()) } else { Err(()) };
+        if result.is_err() && exit_on_error {
+            break;
+        }
+    }
+
+    shell.env.restore_args(saved);
+    result
+}
+
+#[test_case]
+fn test_run_script() {
+    use alloc::vec;
+    use futures_util::FutureExt;
+    use crate::api::str2char;
+
+    let lines = vec![str2char("# arguments"), str2char("echo $# $0 $2 $1"), str2char(""), str2char("nothing"), str2char("echo after")];
+    let args = [String::from("script"), String::from("a"), String::from("b")];
+    let mut shell = Shell::new(0);
+
+    let mut output = Output::Capture(String::new());
+    let result = run_script(&mut shell, lines.clone(), &args, false, &mut output).now_or_never();
+    assert_eq!(result, Some(Ok(())));               // the last line decides
+    assert_eq!(output.take().unwrap(), "2 script b a\nafter\n");
+    assert_eq!(shell.env.get("1"), None);           // the arguments are gone afterwards
+    assert_eq!(shell.env.get("#"), None);
+
+    let mut output = Output::Capture(String::new());
+    let result = run_script(&mut shell, lines, &args, true, &mut output).now_or_never();
+    assert_eq!(result, Some(Err(())));              // '-e' stops at 'nothing'
+    assert_eq!(output.take().unwrap(), "2 script b a\n");
+    assert_eq!(shell.env.status, 127);
+}
\ No newline at end of file
@@ -0,0 +1,47 @@
+use alloc::boxed::Box;
+use core::fmt::Write;
+use crate::println;
+use crate::api::{char2int, str2char};
+use crate::timer::sleep;
+use crate::terminal::args::ArgSpec;
+use super::{Command, CommandFuture, Invocation};
+
+pub struct Sleep;
+
+impl Command for Sleep {
+    fn name(&self) -> &'static str {
+        "sleep"
+    }
+
+    fn help(&self) -> &'static str {
+        "Count the seconds, Ctrl+C interrupts it"
+    }
+
+    fn spec(&self) -> ArgSpec {
+        ArgSpec::new(1, Some(1), "<seconds>")
+    }
+
+    fn run<'a>(&'a self, invocation: Invocation<'a>) -> CommandFuture<'a> {
+        Box::pin(async move {
+            let shell = invocation.shell;
+            match char2int(str2char(&invocation.args.positional[0])) {
+                Ok(n) => {
+                    for i in 0..n {
+                        for _ in 0..20 {    // look for Ctrl+C every tick
+                            if shell.cancelled() {
+                                return Err(());
+                            }
+                            sleep::sleep_ticks(1).await;
+                        }
+                        writeln!(invocation.stdout, "{}", i+1).unwrap();
+                    }
+                    Ok(())
+                },
+                Err(_) => {
+                    println!("Parameter must be number");
+                    Err(())
+                }
+            }
+        })
+    }
+}
@@ -0,0 +1,36 @@
+use alloc::boxed::Box;
+use crate::println;
+use crate::buffer::split;
+use crate::terminal::args::ArgSpec;
+use super::{Command, CommandFuture, Invocation};
+
+pub struct Split;
+
+impl Command for Split {
+    fn name(&self) -> &'static str {
+        "split"
+    }
+
+    fn help(&self) -> &'static str {
+        "Show 2 or 4 terminals at once in panes, Alt+Fn moves between them, 'off' goes back"
+    }
+
+    fn spec(&self) -> ArgSpec {
+        ArgSpec::new(1, Some(1), "<2|4|off>")
+    }
+
+    fn run<'a>(&'a self, invocation: Invocation<'a>) -> CommandFuture<'a> {
+        Box::pin(async move {
+            let panes = match invocation.args.positional[0].as_str() {
+                "2" => 2,
+                "4" => 4,
+                "off" | "1" => 1,
+                other => {
+                    println!("Can't split into {}, try 2, 4 or off", other);
+                    return Err(());
+                }
+            };
+            split::set(panes)
+        })
+    }
+}
\ No newline at end of file
@@ -0,0 +1,33 @@
+use alloc::boxed::Box;
+use crate::println;
+use crate::terminal::args::ArgSpec;
+use super::{Command, CommandFuture, Invocation};
+
+pub struct Unalias;
+
+impl Command for Unalias {
+    fn name(&self) -> &'static str {
+        "unalias"
+    }
+
+    fn help(&self) -> &'static str {
+        "Remove aliases"
+    }
+
+    fn spec(&self) -> ArgSpec {
+        ArgSpec::new(1, None, "<name>...")
+    }
+
+    fn run<'a>(&'a self, invocation: Invocation<'a>) -> CommandFuture<'a> {
+        Box::pin(async move {
+            let mut result = Ok(());
+            for name in invocation.args.positional.iter() {
+                if invocation.shell.aliases.remove(name).is_err() {
+                    println!("No alias called {}", name);
+                    result = Err(());
+                }
+            }
+            result
+        })
+    }
+}
\ No newline at end of file
@@ -1,37 +1,29 @@
 use alloc::vec::Vec;
+use alloc::string::String;
+use crate::{print, println};
+use crate::api::str2char;
+use crate::file::FileType;
+use super::history::History;
+use super::commands::REGISTRY;
 
-pub struct TerminalController<'a> {
+#[derive(Clone)]
+pub struct TerminalController {
     pub inputline: Vec<char>,
-    pub commands: Vec<(&'a str, bool)>,     // if there is a parameter
+    pub cursor: usize,              // where typed characters go, 0..=inputline.len()
+    pub history: History,
     // pub text_state: TextState,
 }
 
-impl TerminalController<'static> {
-    pub fn new() -> TerminalController<'static> {
+impl TerminalController {
+    pub fn new() -> TerminalController {
         TerminalController {
             inputline: Vec::new(),
-            commands: Vec::new(),
+            cursor: 0,
+            history: History::new(),
             // text_state: TextState::UserInput,
         }
     }
 
-    pub fn init(&mut self) {
-        self.commands.push( ("hello", false) );
-        self.commands.push( ("echo", true) );
-        self.commands.push( ("sleep", true) );
-
-        self.commands.push( ("cd", true) );
-        self.commands.push( ("ls", false) );
-        self.commands.push( ("clear", false) );
-        self.commands.push( ("edit", true) );
-        self.commands.push( ("mk", true) );
-        self.commands.push( ("mkdir", true) );
-        self.commands.push( ("rm", true) );
-
-        self.commands.push( ("run", true) );
-
-    }
-
     // pub fn state_switch(&mut self) {
     //     if self.text_state == TextState::UserInput {
     //         self.text_state = TextState::TextEdit;
@@ -41,75 +33,299 @@ impl TerminalController<'static> {
     // }
 
     pub fn pushchar(&mut self, character: char) {
-        self.inputline.push(character);
+        self.insert(&[character]);
+    }
+
+    pub fn insert(&mut self, text: &[char]) {   // type at the cursor, the rest of the line moves right
+        for (i, c) in text.iter().enumerate() {
+            self.inputline.insert(self.cursor + i, *c);
+        }
+        echo(&self.inputline[self.cursor..]);
+        self.cursor += text.len();
+        cursor_left(self.inputline.len() - self.cursor);
+    }
+
+    fn delete_range(&mut self, start: usize, end: usize) {  // remove inputline[start..end], the cursor being inside it
+        if start >= end {
+            return;
+        }
+        cursor_left(self.cursor - start);
+        self.inputline.drain(start..end);
+        echo(&self.inputline[start..]);     // move the rest of the line left and erase its old end
+        erase_below();
+        cursor_left(self.inputline.len() - start);
+        self.cursor = start;
+    }
+
+    pub fn paste(&mut self) {                   // Ctrl+V, the clipboard on one line
+        let text: Vec<char> = crate::terminal::clipboard::paste().iter()
+            .map(|c| if *c == '\n' { ' ' } else { *c })
+            .filter(|c| *c >= ' ' && *c <= '~')
+            .collect();
+        self.insert(&text);
     }
 
     pub fn backspace(&mut self) {
-        if self.inputline.len() > 0 {
-            self.inputline.pop();
+        if self.cursor > 0 {
+            self.delete_range(self.cursor - 1, self.cursor);
+        }
+    }
+
+    pub fn delete(&mut self) {
+        if self.cursor < self.inputline.len() {
+            self.delete_range(self.cursor, self.cursor + 1);
+        }
+    }
+
+    pub fn left(&mut self) {
+        if self.cursor > 0 {
+            self.cursor -= 1;
+            cursor_left(1);
+        }
+    }
+
+    pub fn right(&mut self) {
+        if self.cursor < self.inputline.len() {
+            self.cursor += 1;
+            cursor_right(1);
         }
     }
 
+    pub fn home(&mut self) {                    // Home, Ctrl+A
+        cursor_left(self.cursor);
+        self.cursor = 0;
+    }
+
+    pub fn end(&mut self) {                     // End, Ctrl+E
+        cursor_right(self.inputline.len() - self.cursor);
+        self.cursor = self.inputline.len();
+    }
+
+    pub fn kill_end(&mut self) {                // Ctrl+K
+        self.delete_range(self.cursor, self.inputline.len());
+    }
+
+    pub fn kill_start(&mut self) {              // Ctrl+U
+        self.delete_range(0, self.cursor);
+    }
+
+    pub fn kill_word(&mut self) {               // Ctrl+W, the word before the cursor and the spaces after it
+        let mut start = self.cursor;
+        while start > 0 && self.inputline[start - 1] == ' ' {
+            start -= 1;
+        }
+        while start > 0 && self.inputline[start - 1] != ' ' {
+            start -= 1;
+        }
+        self.delete_range(start, self.cursor);
+    }
+
     pub fn clear(&mut self) {
         self.inputline = Vec::new();
+        self.cursor = 0;
     }
 
-    pub fn retrieve(&self) -> Result< (&str, Vec<char>), () > {        // maybe can be rewritted by 'alloc::str::RSplit'     
-        let mut found: bool = false;
-        let mut if_para: bool = false;
-        let mut command_input: Vec<char> = Vec::new();
-        let mut command_output: &str = "";
-        let mut parameter_output: Vec<char> = Vec::new();
+    pub fn history_previous(&mut self) {        // ArrowUp, replace the typed line with an older one
+        if let Some(line) = self.history.previous(&self.inputline) {
+            self.replace_line(line);
+        }
+    }
 
-        for c in &self.inputline {          // destruct the command from userinput
-            if *c == ' ' {
-                break;
-            } else {
-                command_input.push(*c);
-            }
+    pub fn history_next(&mut self) {            // ArrowDown
+        if let Some(line) = self.history.next() {
+            self.replace_line(line);
         }
+    }
+
+    fn replace_line(&mut self, line: Vec<char>) {   // erase the typed line on the screen and print the new one
+        self.home();
+        self.delete_range(0, self.inputline.len());
+        self.insert(&line);
+    }
 
-        for (comm, para) in &self.commands {
-            let mut matched: bool = true;
+    pub fn complete(&mut self, files: Vec<(Vec<char>, FileType)>, prompt: &str) {  // Tab, complete a command or a file name
+        let start = match self.inputline[..self.cursor].iter().rposition(|c| *c == ' ') {
+            Some(i) => i + 1,
+            None => 0,
+        };
+        let prefix: Vec<char> = self.inputline[start..self.cursor].to_vec();
 
-            for (i, &c) in comm.as_bytes().iter().enumerate() {
-                if command_input.len() != comm.len() || command_input.len() == 0 { // invalid input
-                    matched = false;
-                    break;
+        let mut candidates: Vec<(Vec<char>, bool)> = Vec::new();   // (name, if a space should follow)
+        if start == 0 {                                 // the first word is a command
+            for command in REGISTRY.commands() {
+                let name = str2char(command.name());
+                if name.starts_with(&prefix) {
+                    candidates.push((name, command.spec().max != Some(0)));
                 }
-                if c != command_input[i] as u8 {           // not match
-                    matched = false;
-                    break;
+            }
+        } else {                                        // the others are files in current folder
+            for (name, _) in files {
+                if name.starts_with(&prefix) {
+                    candidates.push((name, false));
                 }
             }
+        }
+        if candidates.len() == 0 {
+            return;
+        }
 
-            match matched {
-                true => {
-                    found = true;
-                    command_output = comm;
-                    if_para = *para;
-                    break;
-                },
-                false => {}
-            };
-        }
-        
-        if found == false {
-            return Err(());
-        } else {
-            if if_para == true {
-                if self.inputline.len() <= command_output.len() + 1 {   // there is no parameter in userinput
-                    return Err(())
-                }
-                
-                for c in &self.inputline[command_output.len()+1..self.inputline.len()] {    // destruct para in userinput
-                    parameter_output.push(*c)
+        let mut common = candidates[0].0.clone();       // the longest common prefix of the candidates
+        for (name, _) in &candidates[1..] {
+            let len = common.iter().zip(name.iter()).take_while(|(a, b)| a == b).count();
+            common.truncate(len);
+        }
+
+        if common.len() > prefix.len() || candidates.len() == 1 {
+            self.insert(&common[prefix.len()..]);
+            if candidates.len() == 1 && candidates[0].1 {
+                self.pushchar(' ');
+            }
+        } else {                                        // ambiguous, list the candidates and retype the line
+            let cursor = self.cursor;
+            self.end();
+            println!("");
+            let len = candidates.len();
+            for (i, (name, _)) in candidates.iter().enumerate() {
+                let name: String = name.iter().collect();
+                if i < len - 1 {
+                    print!("{}  ", name);
+                } else {
+                    println!("{}", name);
                 }
-                
-                return Ok( (&*command_output, parameter_output) );
-            } else {
-                return Ok( (&*command_output, parameter_output) );
             }
+            print!("{}", prompt);
+            echo(&self.inputline);
+            cursor_left(self.inputline.len() - cursor);
+            self.cursor = cursor;
         }
     }
+
+    pub fn expand_history(&mut self) -> Result<bool, ()> {   // replace '!n' by the n-th history entry
+        if self.inputline.len() < 2 || self.inputline[0] != '!' {
+            return Ok(false);
+        }
+
+        use crate::api::char2int;
+        let number = char2int(self.inputline[1..].to_vec())?;
+        match self.history.get(number as usize) {
+            Some(line) => {
+                self.inputline = line.clone();
+                return Ok(true);
+            },
+            None => return Err(()),
+        }
+    }
+
+    pub fn record(&mut self) {
+        self.history.push(self.inputline.clone());
+    }
+}
+
+/* the cursor moves by 'ESC [ n D' and 'ESC [ n C', which go on across the rows of a long line, see ansi.rs */
+fn cursor_left(n: usize) {
+    if n > 0 {                      // 'ESC [ 0 D' would move by one
+        print!("\x1b[{}D", n);
+    }
+}
+
+fn cursor_right(n: usize) {
+    if n > 0 {
+        print!("\x1b[{}C", n);
+    }
+}
+
+fn erase_below() {                  // the line is the last thing on the screen, its old end may have wrapped
+    print!("\x1b[J");
+}
+
+fn echo(text: &[char]) {
+    for c in text {
+        print!("{}", c);
+    }
+}
+
+#[cfg(test)]
+fn typed(line: &str) -> TerminalController {
+    let mut controller = TerminalController::new();
+    controller.inputline = str2char(line);
+    controller.cursor = controller.inputline.len();
+    controller
+}
+
+#[test_case]
+fn test_kill_word() {
+    let mut controller = typed("echo one  two");
+    controller.kill_word();
+    assert_eq!(controller.inputline, str2char("echo one  "));
+    controller.kill_word();                                     // the spaces go with the word before them
+    assert_eq!(controller.inputline, str2char("echo "));
+
+    let mut controller = typed("abc def");
+    controller.cursor = 5;
+    controller.kill_word();                                     // only up to the cursor
+    assert_eq!(controller.inputline, str2char("abc ef"));
+    assert_eq!(controller.cursor, 4);
+}
+
+#[test_case]
+fn test_kill_line() {
+    let mut controller = typed("cp a.txt b.txt");
+    controller.home();
+    for _ in 0..3 {
+        controller.right();
+    }
+    controller.pushchar('-');
+    assert_eq!(controller.inputline, str2char("cp -a.txt b.txt"));
+    controller.kill_end();
+    assert_eq!(controller.inputline, str2char("cp -"));
+    assert_eq!(controller.cursor, 4);
+    controller.left();
+    controller.kill_start();
+    assert_eq!(controller.inputline, str2char("-"));
+    assert_eq!(controller.cursor, 0);
+}
+
+#[test_case]
+fn test_complete_prefix() {
+    let files = alloc::vec![(str2char("readme.txt"), FileType::Document), (str2char("readme.md"), FileType::Document)];
+    let mut controller = typed("cat r");
+    controller.complete(files, "> ");
+    assert_eq!(controller.inputline, str2char("cat readme."));    // as far as both names agree
+    assert_eq!(controller.cursor, controller.inputline.len());
+
+    let mut controller = typed("he");
+    controller.complete(Vec::new(), "> ");
+    assert_eq!(controller.inputline, str2char("hel"));             // hello and help
+}
+
+#[test_case]
+fn test_complete_unique() {
+    let mut controller = typed("unal");
+    controller.complete(Vec::new(), "> ");
+    assert_eq!(controller.inputline, str2char("unalias "));        // a command taking arguments gets a space
+
+    let mut controller = typed("hist");
+    controller.complete(Vec::new(), "> ");
+    assert_eq!(controller.inputline, str2char("history"));         // one taking none doesn't
+
+    let files = alloc::vec![(str2char("report"), FileType::Document), (str2char("docs"), FileType::Folder)];
+    let mut controller = typed("cat rep");
+    controller.complete(files, "> ");
+    assert_eq!(controller.inputline, str2char("cat report"));
+}
+
+#[test_case]
+fn test_complete_ambiguous() {
+    use crate::buffer::terminal_buffer::TERMINAL_WRITERS;
+    use x86_64::instructions::interrupts;
+
+    let mut controller = typed("hel");
+    controller.complete(Vec::new(), "> ");
+    assert_eq!(controller.inputline, str2char("hel"));             // nothing to add, the line stays
+    assert_eq!(controller.cursor, 3);
+    let listed = interrupts::without_interrupts(|| {
+        let writer = TERMINAL_WRITERS[0].lock();
+        (0..writer.line_count()).any(|line| writer.line(line).iter().collect::<String>().contains("hello  help"))
+    });
+    assert!(listed);
 }
\ No newline at end of file
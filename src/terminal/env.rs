@@ -0,0 +1,106 @@
+use alloc::vec::Vec;
+use alloc::string::String;
+use alloc::format;
+
+pub const PATH_SEPARATOR: char = ';';          // between the folders of PATH, e.g. "root;root\Compiler"
+
+/*
+The prompt template, where
+\w is the current path, \l the terminal number, \u the user,
+\t the time as HH:MM:SS, \? the exit status of the last command and \\ a backslash.
+*/
+pub const DEFAULT_PS1: &str = "\\w>";
+
+#[derive(Clone)]
+pub struct Environment {            // the variables of the shell running in one terminal
+    vars: Vec<(String, String)>,
+    pub status: u8,                 // exit status of the last command, read by '$?'
+}
+
+impl Environment {
+    pub fn new() -> Environment {
+        Environment {
+            vars: Vec::new(),
+            status: 0,
+        }
+    }
+
+    pub fn init(&mut self) {
+        self.set("PATH", "root\\Compiler").unwrap();
+        self.set("USER", "root").unwrap();
+        self.set("PS1", DEFAULT_PS1).unwrap();
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.vars.iter().find(|(n, _)| n == name).map(|(_, v)| v.as_str())
+    }
+
+    pub fn expand(&self, name: &str) -> String {    // the text a '$name' stands for, empty if it is not set
+        if name == "?" {
+            return format!("{}", self.status);
+        }
+        String::from(self.get(name).unwrap_or(""))
+    }
+
+    pub fn set(&mut self, name: &str, value: &str) -> Result<(), ()> {
+        if !valid_name(name) {
+            return Err(());
+        }
+        match self.vars.iter_mut().find(|(n, _)| n == name) {
+            Some((_, v)) => *v = String::from(value),
+            None => self.vars.push((String::from(name), String::from(value))),
+        }
+        Ok(())
+    }
+
+    pub fn unset(&mut self, name: &str) -> Result<(), ()> {
+        match self.vars.iter().position(|(n, _)| n == name) {
+            Some(index) => {
+                self.vars.remove(index);
+                Ok(())
+            },
+            None => Err(()),
+        }
+    }
+
+    pub fn set_args(&mut self, args: &[String]) -> Vec<(String, String)> {    // $0..$n and $# of a script, returns the ones of the caller
+        let saved: Vec<(String, String)> = self.vars.iter().filter(|(n, _)| is_arg(n)).cloned().collect();
+        self.vars.retain(|(n, _)| !is_arg(n));
+        for (i, arg) in args.iter().enumerate() {
+            self.vars.push((format!("{}", i), arg.clone()));
+        }
+        self.vars.push((String::from("#"), format!("{}", args.len().saturating_sub(1))));  // $0 is not counted
+        saved
+    }
+
+    pub fn restore_args(&mut self, saved: Vec<(String, String)>) {
+        self.vars.retain(|(n, _)| !is_arg(n));
+        self.vars.extend(saved);
+    }
+
+    pub fn vars(&self) -> &Vec<(String, String)> {
+        &self.vars
+    }
+
+    pub fn path(&self) -> Vec<Vec<Vec<char>>> {     // the folders of PATH, each one split into its names from root
+        match self.get("PATH") {
+            Some(path) => path.split(PATH_SEPARATOR)
+                .filter(|folder| folder.len() > 0)
+                .map(|folder| folder.split('\\').map(|name| name.chars().collect()).collect())
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+}
+
+pub fn is_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+fn is_arg(name: &str) -> bool {
+    name == "#" || name.chars().all(|c| c.is_ascii_digit())
+}
+
+pub fn valid_name(name: &str) -> bool {
+    name.len() > 0 && name.chars().all(is_name_char)
+}
\ No newline at end of file
@@ -0,0 +1,137 @@
+use alloc::vec::Vec;
+use alloc::string::String;
+use core::fmt::Write;
+use crate::api::str2char;
+use crate::file::file_system::FileSystem;
+use crate::println;
+use super::stream::Output;
+
+pub const HISTORY_CAPACITY: usize = 100;
+pub const HISTORY_FILE: &str = ".history";     // history is persisted only if this file exists in the root folder
+
+#[derive(Clone)]
+pub struct History {
+    entries: Vec<Vec<char>>,
+    evicted: usize,                 // number of entries dropped from the front, keeps '!n' numbers stable
+    cursor: Option<usize>,          // index of the entry being recalled by the arrow keys
+    draft: Vec<char>,               // the line typed before the recalling started
+}
+
+impl History {
+    pub fn new() -> History {
+        History {
+            entries: Vec::new(),
+            evicted: 0,
+            cursor: None,
+            draft: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, line: Vec<char>) {
+        self.cursor = None;
+        if line.len() == 0 {
+            return;
+        }
+        if let Some(last) = self.entries.last() {   // don't record the same command twice in a row
+            if *last == line {
+                return;
+            }
+        }
+
+        if self.entries.len() == HISTORY_CAPACITY { // ring behaviour, the oldest one goes away
+            self.entries.remove(0);
+            self.evicted += 1;
+        }
+        self.entries.push(line);
+    }
+
+    pub fn get(&self, number: usize) -> Option<&Vec<char>> {   // 'number' is the one shown by the 'history' command
+        if number <= self.evicted {
+            return None;
+        }
+        self.entries.get(number - self.evicted - 1)
+    }
+
+    pub fn previous(&mut self, current: &Vec<char>) -> Option<Vec<char>> {  // ArrowUp
+        let index = match self.cursor {
+            Some(0) => return None,                 // already at the oldest entry
+            Some(i) => i - 1,
+            None => {
+                if self.entries.len() == 0 {
+                    return None;
+                }
+                self.draft = current.clone();
+                self.entries.len() - 1
+            },
+        };
+        self.cursor = Some(index);
+        Some(self.entries[index].clone())
+    }
+
+    pub fn next(&mut self) -> Option<Vec<char>> {  // ArrowDown
+        match self.cursor {
+            None => None,
+            Some(i) => {
+                if i + 1 < self.entries.len() {
+                    self.cursor = Some(i + 1);
+                    Some(self.entries[i + 1].clone())
+                } else {                            // walked past the newest entry, give back the draft
+                    self.cursor = None;
+                    Some(self.draft.clone())
+                }
+            }
+        }
+    }
+
+    pub fn print(&self, output: &mut Output) {
+        for (i, line) in self.entries.iter().enumerate() {
+            let line: String = line.iter().collect();
+            writeln!(output, "{:>4}  {}", self.evicted + i + 1, line).unwrap();
+        }
+    }
+
+    pub fn load(&mut self, file_system: &FileSystem) {
+        if let Ok(content) = file_system.read_root_file(str2char(HISTORY_FILE)) {
+            for line in content {
+                self.push(line);
+            }
+        }
+    }
+
+    pub fn save(&self, file_system: &mut FileSystem) {
+        if file_system.read_root_file(str2char(HISTORY_FILE)).is_err() {   // persistence is disabled
+            return;
+        }
+        if file_system.edit_root_file(str2char(HISTORY_FILE), self.entries.clone()).is_err() {
+            println!("WARNING: the history could not be saved");
+        }
+    }
+}
+
+#[test_case]
+fn test_history_ring() {
+    use alloc::format;
+
+    let mut history = History::new();
+    for i in 0..HISTORY_CAPACITY + 5 {
+        history.push(str2char(&format!("c{}", i)));
+    }
+    assert_eq!(history.entries.len(), HISTORY_CAPACITY);
+    assert_eq!(history.get(5), None);                     // '!5' was evicted
+    assert_eq!(history.get(6), Some(&str2char("c5")));    // the numbers don't move
+    assert_eq!(history.get(HISTORY_CAPACITY + 5), Some(&str2char(&format!("c{}", HISTORY_CAPACITY + 4))));
+    assert_eq!(history.get(HISTORY_CAPACITY + 6), None);
+}
+
+#[test_case]
+fn test_history_draft() {
+    let mut history = History::new();
+    history.push(str2char("a"));
+    history.push(str2char("b"));
+    assert_eq!(history.previous(&str2char("typed")), Some(str2char("b")));
+    assert_eq!(history.previous(&str2char("b")), Some(str2char("a")));
+    assert_eq!(history.previous(&str2char("a")), None);
+    assert_eq!(history.next(), Some(str2char("b")));
+    assert_eq!(history.next(), Some(str2char("typed")));   // the draft comes back
+    assert_eq!(history.next(), None);
+}
\ No newline at end of file
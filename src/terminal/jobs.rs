@@ -0,0 +1,116 @@
+use alloc::vec::Vec;
+use alloc::string::String;
+use lazy_static::lazy_static;
+use spin::Mutex;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum JobState {
+    Running,
+    Done(u8),                       // the exit status
+    Killed,
+}
+
+#[derive(Debug, Clone)]
+pub struct Job {                    // a command line running in a session
+    pub id: usize,
+    pub session: usize,
+    pub line: String,
+    pub background: bool,           // started with '&'
+    pub waited: bool,               // 'fg' waits for it, so Ctrl+C reaches it too
+    pub state: JobState,
+    cancelled: bool,
+}
+
+struct Jobs {
+    jobs: Vec<Job>,
+    next_id: usize,
+}
+
+lazy_static! {
+    static ref JOBS: Mutex<Jobs> = Mutex::new(Jobs { jobs: Vec::new(), next_id: 1 });
+}
+
+pub fn start(session: usize, line: String, background: bool) -> usize {
+    let mut jobs = JOBS.lock();
+    let id = jobs.next_id;
+    jobs.next_id += 1;
+    jobs.jobs.push(Job { id, session, line, background, waited: false, state: JobState::Running, cancelled: false });
+    id
+}
+
+pub fn finish(id: usize, status: u8) {
+    let mut jobs = JOBS.lock();
+    if let Some(job) = jobs.jobs.iter_mut().find(|j| j.id == id) {
+        job.state = if job.cancelled { JobState::Killed } else { JobState::Done(status) };
+        if !job.background {        // nobody asks for a foreground job afterwards
+            jobs.jobs.retain(|j| j.id != id);
+        }
+    }
+}
+
+pub fn get(id: usize) -> Option<Job> {
+    JOBS.lock().jobs.iter().find(|j| j.id == id).cloned()
+}
+
+/*
+Ask a job to stop. Nothing is stopped by force: the commands and scripts
+check 'is_cancelled' between their steps and give up.
+*/
+pub fn cancel(id: usize) -> Result<(), ()> {
+    match JOBS.lock().jobs.iter_mut().find(|j| j.id == id && j.state == JobState::Running) {
+        Some(job) => {
+            job.cancelled = true;
+            Ok(())
+        },
+        None => Err(()),
+    }
+}
+
+pub fn is_cancelled(id: usize) -> bool {
+    JOBS.lock().jobs.iter().any(|j| j.id == id && j.cancelled)
+}
+
+pub fn interrupt(session: usize) {  // Ctrl+C, cancel what runs in the foreground of a session
+    for job in JOBS.lock().jobs.iter_mut() {
+        if job.session == session && (!job.background || job.waited) && job.state == JobState::Running {
+            job.cancelled = true;
+        }
+    }
+}
+
+pub fn wait(id: usize) -> Result<(), ()> {          // 'fg', Ctrl+C reaches the job from now on
+    match JOBS.lock().jobs.iter_mut().find(|j| j.id == id && j.state == JobState::Running) {
+        Some(job) => {
+            job.waited = true;
+            Ok(())
+        },
+        None => Err(()),
+    }
+}
+
+pub fn forget(id: usize) {          // a finished job whose end has been reported
+    JOBS.lock().jobs.retain(|j| j.id != id);
+}
+
+pub fn list(session: usize) -> Vec<Job> {
+    JOBS.lock().jobs.iter().filter(|j| j.session == session).cloned().collect()
+}
+
+#[test_case]
+fn test_cancel_and_interrupt() {
+    let background = start(5, String::from("sleep 9 &"), true);
+    let foreground = start(5, String::from("sleep 9"), false);
+    interrupt(5);
+    assert!(!is_cancelled(background));
+    assert!(is_cancelled(foreground));
+    finish(foreground, 1);
+    assert!(get(foreground).is_none());
+
+    assert_eq!(wait(background), Ok(()));
+    interrupt(5);
+    finish(background, 1);
+    assert_eq!(get(background).map(|j| j.state), Some(JobState::Killed));
+    assert_eq!(cancel(background), Err(()));
+    forget(background);
+    assert_eq!(list(5).len(), 0);
+}
\ No newline at end of file
@@ -1,44 +1,19 @@
-pub mod command;
+pub mod commands;
 pub mod controller;
-pub mod terminal1;
-pub mod terminal2;
-pub mod task1;
-pub mod task2;
+pub mod shell;
+pub mod history;
+pub mod tokenizer;
+pub mod args;
+pub mod env;
+pub mod alias;
+pub mod pipeline;
+pub mod stream;
+pub mod session;
+pub mod jobs;
+pub mod clipboard;
 
 #[derive(PartialEq, Copy, Clone)]
 pub enum TextState {
     UserInput,
     TextEdit,
-}
-
-#[derive(PartialEq, Copy, Clone, Debug)]
-pub enum SwitchState {
-    Terminal1,
-    Terminal2,
-}
-
-#[derive(Debug)]
-pub struct TerminalSwitchState {
-    state: SwitchState,
-}
-
-impl TerminalSwitchState {
-    pub fn new() -> TerminalSwitchState {
-        TerminalSwitchState {
-            state: SwitchState::Terminal1,
-        }
-    }
-
-    pub fn switch_state(&mut self) -> Result<SwitchState, SwitchState> {
-        if self.state == SwitchState::Terminal1 {
-            self.state = SwitchState::Terminal2;
-        } else {
-            self.state = SwitchState::Terminal1;
-        }
-        return Ok(self.state)
-    }
-
-    pub fn get_state(&self) -> SwitchState {
-        self.state
-    }
 }
\ No newline at end of file
@@ -0,0 +1,90 @@
+use alloc::vec::Vec;
+use alloc::string::String;
+use super::tokenizer::{Token, Word, Operator};
+
+pub struct Redirect {
+    pub file: String,
+    pub append: bool,               // '>>' rather than '>'
+}
+
+pub struct Pipeline {               // a | b | c < input > output &
+    pub stages: Vec<Vec<Word>>,     // the words of every command, its name first
+    pub input: Option<String>,
+    pub output: Option<Redirect>,
+    pub background: bool,           // run as a job while the shell goes on
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PipelineError {
+    EmptyCommand,
+    MissingFile,
+    MisplacedInput,
+    MisplacedOutput,
+    MisplacedBackground,
+}
+
+impl PipelineError {
+    pub fn message(&self) -> &'static str {
+        match self {
+            PipelineError::EmptyCommand => "Empty command in the pipeline",
+            PipelineError::MissingFile => "Missing file name after the redirection",
+            PipelineError::MisplacedInput => "Only the first command can read from a file",
+            PipelineError::MisplacedOutput => "Only the last command can write to a file",
+            PipelineError::MisplacedBackground => "'&' can only end the line",
+        }
+    }
+}
+
+pub fn parse(tokens: Vec<Token>) -> Result<Pipeline, PipelineError> {
+    let mut pipeline = Pipeline { stages: Vec::new(), input: None, output: None, background: false };
+    let mut stage: Vec<Word> = Vec::new();
+    let mut tokens = tokens.into_iter();
+
+    while let Some(token) = tokens.next() {
+        if pipeline.background {        // nothing may follow the '&'
+            return Err(PipelineError::MisplacedBackground);
+        }
+        match token {
+            Token::Word(word) => stage.push(word),
+            Token::Operator(Operator::Pipe) => {
+                if stage.len() == 0 {
+                    return Err(PipelineError::EmptyCommand);
+                }
+                if pipeline.output.is_some() {
+                    return Err(PipelineError::MisplacedOutput);
+                }
+                pipeline.stages.push(stage);
+                stage = Vec::new();
+            },
+            Token::Operator(Operator::Background) => {
+                if stage.len() == 0 {
+                    return Err(PipelineError::EmptyCommand);
+                }
+                pipeline.background = true;
+            },
+            Token::Operator(operator) => {
+                let file = match tokens.next() {
+                    Some(Token::Word(word)) => word.text,
+                    _ => return Err(PipelineError::MissingFile),
+                };
+                if operator == Operator::Read {
+                    if pipeline.stages.len() > 0 {
+                        return Err(PipelineError::MisplacedInput);
+                    }
+                    pipeline.input = Some(file);
+                } else {
+                    pipeline.output = Some(Redirect { file, append: operator == Operator::Append });
+                }
+            }
+        }
+    }
+
+    if stage.len() == 0 {
+        if pipeline.stages.len() == 0 && pipeline.input.is_none() && pipeline.output.is_none() {
+            return Ok(pipeline);        // nothing typed at all
+        }
+        return Err(PipelineError::EmptyCommand);
+    }
+    pipeline.stages.push(stage);
+    return Ok(pipeline);
+}
\ No newline at end of file
@@ -0,0 +1,408 @@
+use alloc::vec::Vec;
+use alloc::string::String;
+use conquer_once::spin::OnceCell;
+use crossbeam_queue::ArrayQueue;
+use crate::{print, println};
+use alloc::boxed::Box;
+use core::{future::Future, pin::Pin, task::{Poll, Context}};
+use futures_util::stream::{Stream, StreamExt};
+use futures_util::task::AtomicWaker;
+use pc_keyboard::{DecodedKey, KeyCode};
+use crate::buffer::keyboard::SWITCH;
+use crate::buffer::ansi::{Action, Parser};
+use crate::buffer::kbd::Decoder;
+use crate::buffer::cp437::Utf8;
+use super::shell::Shell;
+
+pub const SESSIONS: usize = 6;              // virtual consoles, switched with Alt+F1..F6
+pub const SERIAL: usize = SESSIONS;         // the shell on COM1, after the virtual consoles
+
+#[derive(Debug, Clone, Copy)]
+pub struct SessionState {
+    pub printing: bool,             // the output goes to the screen of this session, whichever is shown
+    pub tasking: bool,              // a command line is running, keys are not queued
+    pub editing: bool,              // 'edit' or 'exec' is running, it reads the keys instead of the shell
+    pub task_running: bool,         // cleared by Ctrl+C to drop the rest of the queued work
+    pub scrolled: usize,            // the lines the VGA is scrolled back by Shift+PageUp, 0 at the bottom
+    pub selecting: bool,            // F8, the keys pick text off the VGA, see 'selection'
+}
+
+impl SessionState {
+    pub const fn new() -> SessionState {
+        SessionState {
+            printing: false,
+            tasking: false,
+            editing: false,
+            task_running: true,
+            scrolled: 0,
+            selecting: false,
+        }
+    }
+}
+
+pub static mut STATES: [SessionState; SESSIONS + 1] = [SessionState::new(); SESSIONS + 1];
+
+struct Channel<T> {
+    queue: ArrayQueue<T>,
+    waker: AtomicWaker,
+}
+
+impl<T> Channel<T> {
+    fn new(capacity: usize) -> Channel<T> {
+        Channel {
+            queue: ArrayQueue::new(capacity),
+            waker: AtomicWaker::new(),
+        }
+    }
+
+    fn push(&self, item: T) -> Result<(), ()> {
+        match self.queue.push(item) {
+            Ok(_) => {
+                self.waker.wake();      // notify the executor
+                Ok(())
+            },
+            Err(_) => Err(()),
+        }
+    }
+}
+
+struct ChannelStream<T: 'static> {
+    channel: &'static Channel<T>,
+}
+
+impl<T> Stream for ChannelStream<T> {
+    type Item = T;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<T>> {
+        if let Ok(item) = self.channel.queue.pop() {    // avoid the performance overhead if it's successful to pop
+            return Poll::Ready(Some(item));
+        }
+        self.channel.waker.register(&cx.waker());       // register a Waker
+        match self.channel.queue.pop() {                // pop again
+            Ok(item) => {
+                self.channel.waker.take();              // Waker no longer needed
+                Poll::Ready(Some(item))
+            }
+            Err(crossbeam_queue::PopError) => Poll::Pending,
+        }
+    }
+}
+
+struct Channels {                   // the queues of one session
+    keys: Channel<u8>,                          // scancodes from the keyboard interrupt
+    commands: Channel<(String, String)>,        // work handed over from the shell to the task
+}
+
+static CHANNELS: OnceCell<Vec<Channels>> = OnceCell::uninit();
+
+pub fn init() {                     // before spawning the sessions, so the keyboard interrupt never allocates
+    CHANNELS.try_init_once(|| (0..=SERIAL).map(|_| Channels {
+        keys: Channel::new(100),
+        commands: Channel::new(1000),
+    }).collect()).expect("session::init should only be called once");
+}
+
+fn channels(id: usize) -> Option<&'static Channels> {
+    match CHANNELS.try_get() {
+        Ok(channels) => channels.get(id),
+        Err(_) => None,
+    }
+}
+
+/* called by the keyboard interrupt handler, must not block or allocate */
+pub(crate) fn add_scancode(scancode: u8) {
+    unsafe {
+        if STATES[SWITCH].tasking && !STATES[SWITCH].editing {     // keys typed while a command runs are dropped
+            return;
+        }
+        if STATES[SWITCH].selecting {
+            return;
+        }
+        match channels(SWITCH) {
+            Some(channels) => {
+                if channels.keys.push(scancode).is_err() {
+                    println!("WARNING: scancode queue full; dropping keyboard input");
+                }
+            },
+            None => println!("WARNING: scancode queue uninitialized"),
+        }
+    }
+}
+
+pub(crate) fn add_serial_byte(byte: u8) {     // from the serial task, like 'add_scancode'
+    unsafe {
+        if STATES[SERIAL].tasking && !STATES[SERIAL].editing {
+            return;
+        }
+    }
+    if let Some(channels) = channels(SERIAL) {
+        if channels.keys.push(byte).is_err() {
+            println!("WARNING: serial queue full; dropping serial input");
+        }
+    }
+}
+
+pub fn scancodes(id: usize) -> impl Stream<Item = u8> {    // the keys of a session, for a command reading them while the shell waits
+    ChannelStream { channel: &channels(id).expect("session::init not called").keys }
+}
+
+pub fn try_key(id: usize) -> Option<u8> {  // like 'scancodes', for a command polling the keys between ticks
+    channels(id)?.keys.queue.pop().ok()
+}
+
+pub fn add_command(id: usize, command: (String, String)) {
+    match channels(id) {
+        Some(channels) => {
+            if channels.commands.push(command).is_err() {
+                println!("WARNING: command queue full; dropping the command");
+            }
+        },
+        None => println!("WARNING: command queue uninitialized"),
+    }
+}
+
+/*
+Send whatever a future prints to the screen of a session, whichever is shown,
+for the background jobs which print while their shell reads the keys.
+*/
+pub fn printing_to<F: Future<Output = ()>>(id: usize, future: F) -> PrintingTo<F> {
+    PrintingTo { id, future: Box::pin(future) }
+}
+
+pub struct PrintingTo<F> {
+    id: usize,
+    future: Pin<Box<F>>,
+}
+
+impl<F: Future<Output = ()>> Future for PrintingTo<F> {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<()> {
+        let id = self.id;
+        unsafe {
+            STATES[id].printing = true;
+            let poll = self.future.as_mut().poll(cx);
+            STATES[id].printing = false;
+            poll
+        }
+    }
+}
+
+pub fn screen(id: usize) -> usize { // the address of the screen buffer of a session
+    0xb9000 + id * 0x1000
+}
+
+/*
+One virtual console: a shell reading the keys typed while it is shown,
+and a task doing the work the shell queues so that the keys are still read meanwhile.
+*/
+pub struct TerminalSession {
+    id: usize,
+}
+
+impl TerminalSession {
+    pub fn new(id: usize) -> TerminalSession {
+        TerminalSession { id }
+    }
+
+    pub async fn run_shell(self) {
+        let id = self.id;
+        let mut scancodes = ChannelStream { channel: &channels(id).expect("session::init not called").keys };
+        let mut keyboard = Decoder::new();
+
+        let mut shell = Shell::new(id);
+        shell.autostart().await;
+
+        while let Some(scancode) = scancodes.next().await {
+            if let Some(press) = keyboard.add_byte(scancode) {
+                unsafe {
+                    if SWITCH != id {       // try to run command in the shown session only
+                        continue;
+                    }
+                }
+                if press.alt {              // Alt+key belongs to the keyboard task
+                    continue;
+                }
+                match press.key {
+                    DecodedKey::Unicode(character) => { // normal characters
+                        match character {
+                            '\n' => {
+                                unsafe {
+                                    STATES[id].tasking = true;
+                                    shell.submit().await;
+                                    STATES[id].tasking = false;
+                                }
+                            },
+                            '\t' => shell.complete(),
+                            '\u{03}' => shell.cancel_line(),             // Ctrl+C
+                            '\u{08}' => shell.controller.backspace(),
+                            '\u{7f}' => shell.controller.delete(),
+                            '\u{01}' => shell.controller.home(),          // Ctrl+A
+                            '\u{05}' => shell.controller.end(),           // Ctrl+E
+                            '\u{0b}' => shell.controller.kill_end(),      // Ctrl+K
+                            '\u{15}' => shell.controller.kill_start(),    // Ctrl+U
+                            '\u{17}' => shell.controller.kill_word(),     // Ctrl+W
+                            '\u{16}' => shell.controller.paste(),         // Ctrl+V
+                            c if (c as u32) < 0x20 => {},                   // Esc and the other control keys
+                            _ => shell.controller.pushchar(character),
+                        }
+                    },
+                    DecodedKey::RawKey(key) => {        // other keys on the keyboard
+                        match key {
+                            KeyCode::ArrowUp => shell.controller.history_previous(),
+                            KeyCode::ArrowDown => shell.controller.history_next(),
+                            KeyCode::ArrowLeft => shell.controller.left(),
+                            KeyCode::ArrowRight => shell.controller.right(),
+                            KeyCode::Home => shell.controller.home(),
+                            KeyCode::End => shell.controller.end(),
+                            KeyCode::Delete => shell.controller.delete(),
+                            _ => {}
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /*
+    The shell on the serial port, spawned inside 'printing_to(SERIAL, ..)' so that it prints there.
+    A terminal on the other end sends bytes instead of scancodes: Enter comes as '\r',
+    Backspace as 0x7f or 0x08 and the arrows as escape sequences.
+    */
+    pub async fn run_serial(self) {
+        let id = self.id;
+        let mut bytes = ChannelStream { channel: &channels(id).expect("session::init not called").keys };
+        let mut escape = Parser::new();
+        let mut utf8 = Utf8::new();
+        let mut previous = 0;
+
+        println!("DerBo OS serial console");
+        let mut shell = Shell::new(id);
+        shell.autostart().await;
+
+        while let Some(byte) = bytes.next().await {
+            let last = previous;
+            previous = byte;
+            match escape.advance(byte) {
+                Action::Print(byte) => {
+                    match byte {
+                        b'\n' if last == b'\r' => {},       // "\r\n" is one Enter
+                        b'\r' | b'\n' => {
+                            unsafe {
+                                STATES[id].tasking = true;
+                                shell.submit().await;
+                                STATES[id].tasking = false;
+                            }
+                        },
+                        b'\t' => shell.complete(),
+                        0x03 => shell.cancel_line(),                // Ctrl+C
+                        0x08 | 0x7f => shell.controller.backspace(),
+                        0x01 => shell.controller.home(),            // Ctrl+A
+                        0x05 => shell.controller.end(),             // Ctrl+E
+                        0x0b => shell.controller.kill_end(),        // Ctrl+K
+                        0x15 => shell.controller.kill_start(),      // Ctrl+U
+                        0x17 => shell.controller.kill_word(),       // Ctrl+W
+                        0x16 => shell.controller.paste(),           // Ctrl+V
+                        0x20..=0x7e => shell.controller.pushchar(byte as char),
+                        0x80..=0xff => {                            // a character typed in UTF-8
+                            if let Some(c) = utf8.advance(byte) {
+                                shell.controller.pushchar(c);
+                            }
+                        },
+                        _ => {}
+                    }
+                },
+                Action::Csi(csi) => {
+                    match csi.command {
+                        b'A' => shell.controller.history_previous(),
+                        b'B' => shell.controller.history_next(),
+                        b'C' => shell.controller.right(),
+                        b'D' => shell.controller.left(),
+                        b'H' => shell.controller.home(),
+                        b'F' => shell.controller.end(),
+                        b'~' => match csi.param(0, 0) {     // 'ESC [ n ~', the keys above the arrows
+                            1 | 7 => shell.controller.home(),
+                            3 => shell.controller.delete(),
+                            4 | 8 => shell.controller.end(),
+                            _ => {}
+                        },
+                        _ => {}
+                    }
+                },
+                _ => {}
+            }
+        }
+    }
+
+    pub async fn run_tasks(self) {
+        use crate::timer::sleep;
+
+        let id = self.id;
+        let mut commands = ChannelStream { channel: &channels(id).expect("session::init not called").commands };
+
+        while let Some((command, parameter)) = commands.next().await {
+            unsafe {
+                if command == "sleep" {
+                    if STATES[id].task_running == false {
+                        continue;
+                    }
+
+                    sleep::sleep_ticks(20).await;
+                } else if command == "print" {
+                    if STATES[id].task_running == false {
+                        continue;
+                    }
+
+                    STATES[id].printing = true;
+                    print!("{}", parameter);
+                    STATES[id].printing = false;
+                } else if command == "println" {
+                    if STATES[id].task_running == false {
+                        continue;
+                    }
+
+                    STATES[id].printing = true;
+                    println!("{}", parameter);
+                    STATES[id].printing = false;
+                } else if command == "over" {
+                    STATES[id].task_running = true;
+                    STATES[id].printing = true;
+                    print!("{}", parameter);
+                    STATES[id].printing = false;
+                }
+            }
+        }
+    }
+}
+
+#[test_case]
+fn test_keys_follow_switch() {
+    use futures_util::FutureExt;
+
+    if CHANNELS.try_get().is_err() {
+        init();
+    }
+    unsafe {
+        SWITCH = 2;
+        add_scancode(0x1e);
+        assert_eq!(try_key(2), Some(0x1e));         // the shown session gets the key
+        assert_eq!(try_key(0), None);
+
+        STATES[2].tasking = true;
+        add_scancode(0x1e);
+        assert_eq!(try_key(2), None);               // not while a command runs
+        STATES[2].editing = true;
+        add_scancode(0x1e);
+        assert_eq!(try_key(2), Some(0x1e));         // unless the command reads the keys
+        STATES[2] = SessionState::new();
+        SWITCH = 0;
+    }
+
+    add_serial_byte(b'a');
+    assert_eq!(try_key(SERIAL), Some(b'a'));
+    assert_eq!(try_key(0), None);
+
+    let printing = printing_to(3, async { assert!(unsafe { STATES[3].printing }) }).now_or_never();
+    assert_eq!(printing, Some(()));
+    assert!(!unsafe { STATES[3].printing });
+}
@@ -0,0 +1,335 @@
+use alloc::vec::Vec;
+use alloc::string::String;
+use alloc::format;
+use core::fmt::Write;
+use crate::println;
+use crate::task::{Task, executor};
+use crate::api::str2char;
+use crate::file::FileType;
+use crate::file::file_system::FileSystem;
+use super::env::{Environment, DEFAULT_PS1};
+use super::alias::Aliases;
+use crate::timer::rtc;
+use super::controller::TerminalController;
+use super::commands::{Invocation, CommandError, REGISTRY};
+use super::commands::sh::run_script;
+use super::tokenizer::tokenize;
+use super::pipeline::{self, Pipeline};
+use super::stream::{Output, text2lines, lines2text};
+use super::session::{self, STATES};
+use super::jobs::{self, JobState};
+use crate::buffer::keyboard::SWITCH;
+use crate::buffer::status_bar;
+
+pub const AUTOSTART_FILE: &str = ".autostart";   // run by every terminal when it starts, if it exists in the root folder
+
+#[derive(Clone)]
+pub struct Shell {                  // the state of the shell running in one terminal
+    pub id: usize,                  // the session it runs in
+    pub job: Option<usize>,         // the job of the line being run, see 'cancelled'
+    pub background: bool,           // the copy a job of '&' works on, see 'check_background'
+    pub controller: TerminalController,
+    pub file_system: FileSystem,
+    pub env: Environment,
+    pub aliases: Aliases,
+}
+
+impl Shell {
+    pub fn new(id: usize) -> Shell {
+        let mut file_system = FileSystem::new();
+        file_system.init();
+        let mut controller = TerminalController::new();
+        controller.history.load(&file_system);
+        let mut env = Environment::new();
+        env.init();
+
+        Shell {
+            id,
+            job: None,
+            background: false,
+            controller,
+            file_system,
+            env,
+            aliases: Aliases::new(),
+        }
+    }
+
+    pub async fn submit(&mut self) {    // run the typed line when 'Enter' is pressed
+        self.controller.end();
+        println!("");
+        match self.controller.expand_history() {
+            Ok(expanded) => {
+                if expanded {               // show the command picked from history
+                    let line: String = self.controller.inputline.iter().collect();
+                    println!("{}", line);
+                }
+                self.controller.record();
+                self.controller.history.save(&mut self.file_system);
+            },
+            Err(_) => {}
+        }
+
+        let line = self.controller.inputline.clone();
+        let job = jobs::start(self.id, line.iter().collect(), false);
+        self.job = Some(job);
+        let result = self.execute(&line, &mut Output::Console).await;
+        self.env.status = match result {
+            _ if self.cancelled() => {
+                unsafe {
+                    STATES[self.id].task_running = false;   // drop what the line has queued too
+                    STATES[self.id].printing = true;
+                    println!("Interrupted by Ctrl+C");
+                    STATES[self.id].printing = false;
+                }
+                130
+            },
+            Ok(_) => 0,
+            Err(None) => 1,             // the command has told what went wrong
+            Err(Some(error)) => {
+                let message = if line.first() == Some(&'!') {
+                    String::from("No such history entry")
+                } else {
+                    error.message()
+                };
+                self.queue("println", message);
+                error.status()
+            }
+        };
+        jobs::finish(job, self.env.status);
+        self.job = None;
+
+        self.queue("over", self.prompt());
+        self.controller.clear();
+    }
+
+    pub async fn execute(&mut self, line: &[char], output: &mut Output) -> Result<(), Option<CommandError>> {
+        let tokens = tokenize(line, &self.env).map_err(|e| Some(CommandError::Syntax(e)))?;
+        let tokens = self.aliases.expand(tokens, &self.env).map_err(|e| Some(CommandError::Syntax(e)))?;
+        let pipeline = pipeline::parse(tokens).map_err(|e| Some(CommandError::Pipeline(e)))?;
+        if pipeline.stages.len() == 0 {
+            return Err(Some(CommandError::NotFound));
+        }
+
+        if pipeline.background {
+            self.check_background(&pipeline).map_err(Some)?;
+            self.background(line, pipeline, output);
+            return Ok(());
+        }
+        self.run_pipeline(pipeline, output).await
+    }
+
+    /*
+    Run a line like 'a < in | b | c >> out'.
+    Every command is checked before the first one starts, the output of each command
+    becomes the input of the next one and the last one writes to 'output' or the file.
+    */
+    async fn run_pipeline(&mut self, pipeline: Pipeline, output: &mut Output) -> Result<(), Option<CommandError>> {
+        if self.background {                // a line of a script the job runs
+            self.check_background(&pipeline).map_err(Some)?;
+        }
+        let mut commands = Vec::new();
+        for words in pipeline.stages {
+            commands.push(REGISTRY.resolve(words).map_err(Some)?);
+        }
+
+        let mut stdin = match pipeline.input {
+            Some(file) => match self.file_system.read_file(str2char(&file), false) {
+                Ok(content) => Some(lines2text(&content)),
+                Err(_) => return Err(None),
+            },
+            None => None,
+        };
+
+        let count = commands.len();
+        for (i, (command, args)) in commands.into_iter().enumerate() {
+            if self.cancelled() {
+                return Err(None);
+            }
+            let mut capture = Output::Capture(String::new());
+            let stdout = if i == count - 1 && pipeline.output.is_none() {
+                &mut *output
+            } else {
+                &mut capture
+            };
+            let result = command.run(Invocation {
+                shell: &mut *self,
+                args,
+                stdin: stdin.take(),
+                stdout,
+            }).await;
+            if result.is_err() {
+                return Err(None);
+            }
+            stdin = capture.take();
+        }
+
+        if let Some(redirect) = pipeline.output {
+            let text = stdin.unwrap_or(String::new());
+            self.file_system.write_file(str2char(&redirect.file), text2lines(&text), redirect.append).map_err(|_| None)?;
+        }
+        Ok(())
+    }
+
+    /*
+    Run a line ending with '&' as a job on the executor and return at once.
+    The job works on a copy of the shell, like a subshell: the folder and variables
+    it changes are its own. It prints to this terminal while it runs.
+    */
+    fn background(&self, line: &[char], pipeline: Pipeline, output: &mut Output) {
+        let text: String = line.iter().collect();
+        let id = jobs::start(self.id, text.clone(), true);
+        let mut shell = self.clone();
+        shell.job = Some(id);
+        shell.background = true;
+        writeln!(output, "[{}]", id).unwrap();
+
+        executor::spawn(Task::new(session::printing_to(self.id, async move {
+            let status = match shell.run_pipeline(pipeline, &mut Output::Console).await {
+                Ok(_) => 0,
+                Err(None) => 1,
+                Err(Some(error)) => {
+                    println!("{}", error.message());
+                    error.status()
+                }
+            };
+            jobs::finish(id, status);
+            match jobs::get(id) {
+                Some(job) if !job.waited => {       // 'fg' reports the jobs it waits for itself
+                    let state = if job.state == JobState::Killed { "Killed" } else { "Done" };
+                    println!("[{}] {}  {}", id, state, text);
+                    if unsafe { SWITCH } != shell.id {      // the terminal isn't shown, tell on the status bar
+                        status_bar::notify(&format!("[{}] {} in terminal {}", id, state, shell.id + 1));
+                    }
+                    jobs::forget(id);
+                },
+                _ => {}
+            }
+        })));
+    }
+
+    /* the documents of the copy would be lost with it, so a job can't write a file or run a command which changes them */
+    fn check_background(&self, pipeline: &Pipeline) -> Result<(), CommandError> {
+        let changes = pipeline.stages.iter().any(|words| match words.get(0) {
+            Some(name) => REGISTRY.find(&name.text).map_or(false, |command| command.changes_files()),
+            None => false,
+        });
+        if changes || pipeline.output.is_some() {
+            return Err(CommandError::Background);
+        }
+        Ok(())
+    }
+
+    pub fn cancelled(&self) -> bool {   // Ctrl+C or 'kill' asked the running line to stop
+        match self.job {
+            Some(job) => jobs::is_cancelled(job),
+            None => false,
+        }
+    }
+
+    pub async fn autostart(&mut self) {
+        if let Ok(lines) = self.file_system.read_root_file(str2char(AUTOSTART_FILE)) {
+            let mut output = Output::Capture(String::new());    // printed by the task so that it lands in this terminal
+            let _ = run_script(self, lines, &[String::from(AUTOSTART_FILE)], false, &mut output).await;
+            match output.take() {
+                Some(text) if text.len() > 0 => self.queue("print", text),
+                _ => {}
+            }
+        }
+        self.queue("over", self.prompt());
+    }
+
+    pub fn prompt(&self) -> String {    // PS1 filled in, see env::DEFAULT_PS1
+        let template: Vec<char> = self.env.get("PS1").unwrap_or(DEFAULT_PS1).chars().collect();
+        let mut prompt = String::new();
+        let mut i = 0;
+        while i < template.len() {
+            if template[i] == '\\' && i + 1 < template.len() {
+                i += 1;
+                match template[i] {
+                    'w' => prompt.push_str(&self.file_system.get_folder()),
+                    'l' => prompt.push_str(&format!("{}", self.id + 1)),
+                    'u' => prompt.push_str(self.env.get("USER").unwrap_or("")),
+                    't' => {
+                        let (hour, minute, second) = rtc::now();
+                        prompt.push_str(&format!("{:02}:{:02}:{:02}", hour, minute, second));
+                    },
+                    '?' => prompt.push_str(&format!("{}", self.env.status)),
+                    '\\' => prompt.push('\\'),
+                    other => {          // not an escape, keep it as it is
+                        prompt.push('\\');
+                        prompt.push(other);
+                    }
+                }
+            } else {
+                prompt.push(template[i]);
+            }
+            i += 1;
+        }
+        prompt
+    }
+
+    pub fn find_script(&mut self, name: &str) -> Result<Vec<Vec<char>>, ()> {  // a document in the current folder, or else in a folder of PATH
+        let name = str2char(name);
+        if self.file_system.names().iter().any(|(n, t)| *n == name && *t == FileType::Document) {
+            return self.file_system.read_file(name, false);
+        }
+        for folder in self.env.path() {
+            if let Ok(content) = self.file_system.read_folder_file(folder, name.clone()) {
+                return Ok(content);
+            }
+        }
+        let name: String = name.iter().collect();
+        println!("No file called {}", name);
+        Err(())
+    }
+
+    pub fn cancel_line(&mut self) {     // Ctrl+C while typing, drop the line and the work still queued
+        unsafe {
+            STATES[self.id].task_running = false;
+        }
+        self.controller.end();
+        println!("^C");
+        self.controller.clear();
+        self.queue("over", self.prompt());
+    }
+
+    pub fn complete(&mut self) {        // 'Tab' is pressed
+        let prompt = self.prompt();
+        self.controller.complete(self.file_system.names(), &prompt);
+    }
+
+    pub fn queue(&self, command: &str, parameter: String) {    // hand over to the task of this terminal
+        unsafe {
+            let printing = STATES[self.id].printing;   // set all along while a background job runs
+            STATES[self.id].printing = true;
+            session::add_command(self.id, (String::from(command), parameter));
+            STATES[self.id].printing = printing;
+        }
+    }
+}
+
+#[test_case]
+fn test_prompt() {
+    let mut shell = Shell::new(1);
+    assert_eq!(shell.prompt(), format!("{}>", shell.file_system.get_folder()));
+
+    shell.env.set("PS1", "[\\l \\u \\?] \\\\ \\x $").unwrap();
+    shell.env.status = 3;
+    assert_eq!(shell.prompt(), "[2 root 3] \\ \\x $");   // an unknown escape is kept
+    shell.env.unset("PS1").unwrap();
+    assert_eq!(shell.prompt(), format!("{}>", shell.file_system.get_folder()));   // the default one
+}
+
+#[test_case]
+fn test_background_changes() {
+    use futures_util::FutureExt;
+
+    let mut shell = Shell::new(1);
+    let mut output = Output::Capture(String::new());
+    for line in ["mk x &", "ls > out &", "echo a | rm x &"].iter() {
+        let result = shell.execute(&str2char(line), &mut output).now_or_never().unwrap();
+        assert!(matches!(result, Err(Some(CommandError::Background))), "{}", line);
+    }
+    let pipeline = pipeline::parse(tokenize(&str2char("ls | cat &"), &shell.env).unwrap()).unwrap();
+    assert!(shell.check_background(&pipeline).is_ok());
+}
\ No newline at end of file
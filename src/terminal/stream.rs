@@ -0,0 +1,52 @@
+use alloc::vec::Vec;
+use alloc::string::String;
+use core::fmt;
+use crate::print;
+
+pub enum Output {                   // where a command writes its output
+    Console,
+    Capture(String),                // kept for a pipe or a '>' redirection
+}
+
+impl Output {
+    pub fn is_console(&self) -> bool {
+        match self {
+            Output::Console => true,
+            Output::Capture(_) => false,
+        }
+    }
+
+    pub fn take(self) -> Option<String> {
+        match self {
+            Output::Console => None,
+            Output::Capture(text) => Some(text),
+        }
+    }
+}
+
+impl fmt::Write for Output {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        match self {
+            Output::Console => print!("{}", s),
+            Output::Capture(text) => text.push_str(s),
+        }
+        Ok(())
+    }
+}
+
+pub fn text2lines(text: &str) -> Vec<Vec<char>> {   // file content from captured text, one line per '\n'
+    let mut lines: Vec<Vec<char>> = text.split('\n').map(|line| line.chars().collect()).collect();
+    if text.ends_with('\n') {
+        lines.pop();
+    }
+    lines
+}
+
+pub fn lines2text(lines: &Vec<Vec<char>>) -> String {   // captured text from file content
+    let mut text = String::new();
+    for line in lines {
+        text.extend(line.iter());
+        text.push('\n');
+    }
+    text
+}
\ No newline at end of file
@@ -0,0 +1,231 @@
+use alloc::vec::Vec;
+use alloc::string::String;
+use super::env::{Environment, is_name_char};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Word {
+    pub text: String,
+    pub quoted: bool,           // a quoted word is never taken as a '-flag' or an operator
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Operator {
+    Pipe,                       // |
+    Write,                      // >
+    Append,                     // >>
+    Read,                       // <
+    Background,                 // &
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Token {
+    Word(Word),
+    Operator(Operator),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TokenizeError {
+    UnterminatedQuote,
+    TrailingEscape,
+    UnterminatedVariable,
+}
+
+impl TokenizeError {
+    pub fn message(&self) -> &'static str {
+        match self {
+            TokenizeError::UnterminatedQuote => "Unterminated quote",
+            TokenizeError::TrailingEscape => "Nothing to escape after '\\'",
+            TokenizeError::UnterminatedVariable => "Missing '}' after '${'",
+        }
+    }
+}
+
+/*
+Split a command line into words.
+Words are separated by spaces, 'single quotes' keep everything literally,
+"double quotes" allow the escapes \" and \\, and a '\' outside quotes escapes the next character.
+Unquoted '|', '>', '>>', '<' and '&' are operators even without spaces around them.
+$NAME, ${NAME}, $? and $# are replaced by their values outside single quotes, the value stays in one word.
+*/
+pub fn tokenize(line: &[char], env: &Environment) -> Result<Vec<Token>, TokenizeError> {
+    let mut tokens: Vec<Token> = Vec::new();
+    let mut current = String::new();
+    let mut in_word = false;        // distinguish an empty quoted word "" from no word at all
+    let mut quoted = false;
+    let mut i = 0;
+
+    while i < line.len() {
+        let c = line[i];
+        match c {
+            ' ' | '|' | '>' | '<' | '&' => {
+                if in_word {
+                    tokens.push(Token::Word(Word { text: current.clone(), quoted }));
+                    current.clear();
+                    in_word = false;
+                    quoted = false;
+                }
+                match c {
+                    '|' => tokens.push(Token::Operator(Operator::Pipe)),
+                    '<' => tokens.push(Token::Operator(Operator::Read)),
+                    '&' => tokens.push(Token::Operator(Operator::Background)),
+                    '>' => {
+                        if i + 1 < line.len() && line[i + 1] == '>' {
+                            i += 1;
+                            tokens.push(Token::Operator(Operator::Append));
+                        } else {
+                            tokens.push(Token::Operator(Operator::Write));
+                        }
+                    },
+                    _ => {}
+                }
+            },
+            '\'' => {
+                in_word = true;
+                quoted = true;
+                loop {
+                    i += 1;
+                    if i >= line.len() {
+                        return Err(TokenizeError::UnterminatedQuote);
+                    }
+                    if line[i] == '\'' {
+                        break;
+                    }
+                    current.push(line[i]);
+                }
+            },
+            '"' => {
+                in_word = true;
+                quoted = true;
+                loop {
+                    i += 1;
+                    if i >= line.len() {
+                        return Err(TokenizeError::UnterminatedQuote);
+                    }
+                    match line[i] {
+                        '"' => break,
+                        '$' => {
+                            match variable(line, &mut i)? {
+                                Some(name) => current.push_str(&env.expand(&name)),
+                                None => current.push('$'),
+                            }
+                        },
+                        '\\' if i + 1 < line.len() && (line[i + 1] == '"' || line[i + 1] == '\\') => {
+                            i += 1;
+                            current.push(line[i]);
+                        },
+                        other => current.push(other),
+                    }
+                }
+            },
+            '\\' => {
+                if i + 1 >= line.len() {
+                    return Err(TokenizeError::TrailingEscape);
+                }
+                i += 1;
+                in_word = true;
+                quoted = true;
+                current.push(line[i]);
+            },
+            '$' => {
+                match variable(line, &mut i)? {
+                    Some(name) => {
+                        let value = env.expand(&name);
+                        if value.len() > 0 {    // an empty variable alone is no word at all
+                            in_word = true;
+                            current.push_str(&value);
+                        }
+                    },
+                    None => {
+                        in_word = true;
+                        current.push('$');
+                    }
+                }
+            },
+            _ => {
+                in_word = true;
+                current.push(c);
+            }
+        }
+        i += 1;
+    }
+
+    if in_word {
+        tokens.push(Token::Word(Word { text: current, quoted }));
+    }
+
+    return Ok(tokens);
+}
+
+fn variable(line: &[char], i: &mut usize) -> Result<Option<String>, TokenizeError> {   // the name after the '$' at line[i], i is left on its last character
+    let start = *i + 1;
+    if start >= line.len() {
+        return Ok(None);
+    }
+    match line[start] {
+        '?' | '#' => {
+            *i = start;
+            Ok(Some(line[start..start + 1].iter().collect()))
+        },
+        '{' => {
+            let mut end = start + 1;
+            while end < line.len() && line[end] != '}' {
+                end += 1;
+            }
+            if end >= line.len() {
+                return Err(TokenizeError::UnterminatedVariable);
+            }
+            *i = end;
+            Ok(Some(line[start + 1..end].iter().collect()))
+        },
+        c if is_name_char(c) => {
+            let mut end = start;
+            while end < line.len() && is_name_char(line[end]) {
+                end += 1;
+            }
+            *i = end - 1;
+            Ok(Some(line[start..end].iter().collect()))
+        },
+        _ => Ok(None),              // a lonely '$' is kept
+    }
+}
+
+#[test_case]
+fn test_tokenize_quotes_and_escapes() {
+    use crate::api::str2char;
+
+    let env = Environment::new();
+    let tokens = tokenize(&str2char("echo 'a  b' \"c \\\"d\\\"\" e\\ f -n"), &env).unwrap();
+    let texts: Vec<&str> = tokens.iter().map(|t| match t {
+        Token::Word(word) => word.text.as_str(),
+        Token::Operator(_) => "",
+    }).collect();
+    assert_eq!(texts, ["echo", "a  b", "c \"d\"", "e f", "-n"]);
+    assert_eq!(tokens[4], Token::Word(Word { text: String::from("-n"), quoted: false }));
+    assert_eq!(tokenize(&str2char("echo \"abc"), &env), Err(TokenizeError::UnterminatedQuote));
+}
+
+#[test_case]
+fn test_tokenize_operators() {
+    use crate::api::str2char;
+
+    let tokens = tokenize(&str2char("ls|cat >>out '>'&"), &Environment::new()).unwrap();
+    assert_eq!(tokens[1], Token::Operator(Operator::Pipe));
+    assert_eq!(tokens[3], Token::Operator(Operator::Append));
+    assert_eq!(tokens[5], Token::Word(Word { text: String::from(">"), quoted: true }));
+    assert_eq!(tokens[6], Token::Operator(Operator::Background));
+}
+
+#[test_case]
+fn test_tokenize_variables() {
+    use crate::api::str2char;
+
+    let mut env = Environment::new();
+    env.set("A", "x y").unwrap();
+    env.status = 1;
+    let tokens = tokenize(&str2char("$A \"${A}z\" '$A' $? $B $"), &env).unwrap();
+    let texts: Vec<&str> = tokens.iter().map(|t| match t {
+        Token::Word(word) => word.text.as_str(),
+        Token::Operator(_) => "",
+    }).collect();
+    assert_eq!(texts, ["x y", "x yz", "$A", "1", "$"]);
+}
\ No newline at end of file
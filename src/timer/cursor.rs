@@ -1,6 +1,6 @@
 use conquer_once::spin::OnceCell;
 use crossbeam_queue::ArrayQueue;
-use crate::{print, println};
+use crate::println;
 use core::{pin::Pin, task::{Poll, Context}};
 use futures_util::stream::{Stream, StreamExt};
 use futures_util::task::AtomicWaker;
@@ -58,9 +58,9 @@ pub async fn print_timerfifo() {
 
     while let Some(data) = fifodata.next().await {
         match data {
-            0x01 => {                   // use an ascii of 0x01 to tell the WRITER to blink the cursor
-                let c = 0x01 as char;
-                print!("{}", c);
+            0x01 => {                   // blink the cursor
+                crate::buffer::vga_buffer::blink_cursor();
+                crate::buffer::status_bar::render();
             },
             // _ => ()
             _ => println!("{}", data)
@@ -1,2 +1,3 @@
 pub mod cursor;
-pub mod sleep;
\ No newline at end of file
+pub mod sleep;
+pub mod rtc;
\ No newline at end of file
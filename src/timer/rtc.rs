@@ -0,0 +1,35 @@
+use x86_64::instructions::port::Port;
+
+const CMOS_ADDRESS: u16 = 0x70;
+const CMOS_DATA: u16 = 0x71;
+
+fn read_register(register: u8) -> u8 {
+    let mut address: Port<u8> = Port::new(CMOS_ADDRESS);
+    let mut data: Port<u8> = Port::new(CMOS_DATA);
+    unsafe {
+        address.write(register);
+        data.read()
+    }
+}
+
+fn bcd2bin(value: u8) -> u8 {
+    (value & 0x0f) + (value >> 4) * 10
+}
+
+pub fn now() -> (u8, u8, u8) {      // hours, minutes and seconds of the CMOS real time clock
+    while read_register(0x0a) & 0x80 != 0 {}    // wait until it is not updating
+    let mut second = read_register(0x00);
+    let mut minute = read_register(0x02);
+    let mut hour = read_register(0x04);
+    let status = read_register(0x0b);
+
+    if status & 0x04 == 0 {                     // BCD mode
+        second = bcd2bin(second);
+        minute = bcd2bin(minute);
+        hour = bcd2bin(hour & 0x7f) | (hour & 0x80);
+    }
+    if status & 0x02 == 0 && hour & 0x80 != 0 { // 12-hour mode, PM
+        hour = ((hour & 0x7f) + 12) % 24;
+    }
+    (hour & 0x7f, minute, second)
+}
\ No newline at end of file
@@ -1,11 +1,21 @@
+use alloc::vec::Vec;
 use conquer_once::spin::OnceCell;
 use crossbeam_queue::ArrayQueue;
-use core::{pin::Pin, task::{Poll, Context}};
+use core::{future::Future, pin::Pin, task::{Poll, Context, Waker}};
+use core::sync::atomic::{AtomicU64, Ordering};
+use lazy_static::lazy_static;
+use spin::Mutex;
+use crate::interrupts::TIMER_COUNT;
 use futures_util::stream::{Stream, StreamExt};
 use futures_util::task::AtomicWaker;
 
 static TIMER_FIFO: OnceCell<ArrayQueue<u64>> = OnceCell::uninit();   // use a OnceCell to wrap it to initialize at compile time rather than using ArrayQeueu::new()
 static WAKER: AtomicWaker = AtomicWaker::new();
+static NEXT_SLEEP: AtomicU64 = AtomicU64::new(0);
+
+lazy_static! {
+    static ref SLEEPERS: Mutex<Vec<(u64, u64, Waker)>> = Mutex::new(Vec::new());   // the id and deadline of every pending Sleep and its task
+}
 
 pub struct TimerFifoStream {
     _private: (),
@@ -94,4 +104,88 @@ pub async fn sleep_timerfifo_005s(times: u64) {
             _ => (),
         }
     }
+}
+
+/*
+Wait until a number of timer ticks (about 0.05s each) has passed.
+Unlike the timer fifo, any number of tasks can sleep at the same time.
+*/
+pub fn sleep_ticks(ticks: u64) -> Sleep {
+    Sleep {
+        id: NEXT_SLEEP.fetch_add(1, Ordering::Relaxed),
+        deadline: now() + ticks,
+    }
+}
+
+pub struct Sleep {
+    id: u64,                        // finds its entry in SLEEPERS again, one per Sleep however often it's polled
+    deadline: u64,
+}
+
+impl Future for Sleep {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<()> {
+        use x86_64::instructions::interrupts;
+
+        interrupts::without_interrupts(|| {     // the timer interrupt must not find the lock taken
+            let now = now();
+            let mut sleepers = SLEEPERS.lock();
+            sleepers.retain(|(_, deadline, _)| *deadline > now);  // their tasks have been woken already
+            if self.deadline <= now {
+                return Poll::Ready(());
+            }
+            match sleepers.iter_mut().find(|(id, _, _)| *id == self.id) {
+                Some((_, _, waker)) => {
+                    if !waker.will_wake(cx.waker()) {   // polled again from another task
+                        *waker = cx.waker().clone();
+                    }
+                },
+                None => sleepers.push((self.id, self.deadline, cx.waker().clone())),
+            }
+            Poll::Pending
+        })
+    }
+}
+
+impl Drop for Sleep {
+    fn drop(&mut self) {            // a sleep given up early, e.g. by Ctrl+C, leaves nothing behind
+        use x86_64::instructions::interrupts;
+
+        interrupts::without_interrupts(|| {
+            SLEEPERS.lock().retain(|(id, _, _)| *id != self.id);
+        });
+    }
+}
+
+fn now() -> u64 {
+    unsafe { TIMER_COUNT }
+}
+
+/* called by the timer interrupt handler, must not block or allocate, so the wakers are dropped by Sleep::poll */
+pub(crate) fn wake_sleepers(now: u64) {
+    if let Some(sleepers) = SLEEPERS.try_lock() {
+        for (_, deadline, waker) in sleepers.iter() {
+            if *deadline <= now {
+                waker.wake_by_ref();
+            }
+        }
+    }
+}
+
+#[test_case]
+fn test_sleep_registers_once() {
+    use futures_util::task::noop_waker_ref;
+    use x86_64::instructions::interrupts;
+
+    let entries = |id| interrupts::without_interrupts(|| SLEEPERS.lock().iter().filter(|(other, _, _)| *other == id).count());
+    let mut context = Context::from_waker(noop_waker_ref());
+    let mut sleep = sleep_ticks(1000);
+    let id = sleep.id;
+    for _ in 0..3 {
+        assert_eq!(Pin::new(&mut sleep).poll(&mut context), Poll::Pending);
+    }
+    assert_eq!(entries(id), 1);
+    drop(sleep);
+    assert_eq!(entries(id), 0);
 }
\ No newline at end of file
@@ -0,0 +1,86 @@
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(DerBo_OS::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+
+extern crate alloc;
+
+use bootloader::{entry_point, BootInfo};
+use core::panic::PanicInfo;
+use DerBo_OS::memory::{self, AddressSpace, USER_START};
+use DerBo_OS::task::thread;
+use x86_64::VirtAddr;
+use x86_64::registers::control::Cr3;
+use x86_64::structures::paging::PageTableFlags;
+
+entry_point!(main);
+
+fn main(boot_info: &'static BootInfo) -> ! {
+    use DerBo_OS::allocator;
+    use DerBo_OS::memory::BootInfoFrameAllocator;
+
+    DerBo_OS::init();
+    let phys_mem_offset = VirtAddr::new(boot_info.physical_memory_offset);
+    let mut mapper = unsafe { memory::init(phys_mem_offset) };
+    let mut frame_allocator = unsafe { BootInfoFrameAllocator::init(&boot_info.memory_map) };
+    allocator::init_heap(&mut mapper, &mut frame_allocator).expect("heap initialization failed");
+    memory::keep(mapper, frame_allocator);
+    thread::init();
+
+    test_main();
+    loop {}
+}
+
+#[test_case]
+fn map_write_and_switch() {
+    let mut space = AddressSpace::new().expect("no frame for the table");
+    let start = VirtAddr::new(USER_START);
+    space.map(start, 4096 * 2, PageTableFlags::WRITABLE).unwrap();
+    space.write(start + 4094u64, b"split").unwrap();    // across the two pages
+    assert!(space.translate(start + 4096u64).is_some());
+
+    let (kernel, _) = Cr3::read();
+    space.switch();
+    let read = unsafe { core::slice::from_raw_parts((USER_START + 4094) as *const u8, 5) };
+    assert_eq!(read, b"split");
+    thread::set_table(kernel);
+}
+
+#[test_case]
+fn kernel_part_refused() {
+    let mut space = AddressSpace::new().unwrap();
+    assert!(space.map(VirtAddr::new(0x1000), 4096, PageTableFlags::WRITABLE).is_err());
+    assert!(space.unmap(VirtAddr::new(0xb8000), 4096).is_err());
+}
+
+#[test_case]
+fn unmap_and_drop() {
+    let free = memory::free_frames();
+    let mut space = AddressSpace::new().unwrap();
+    let start = VirtAddr::new(USER_START);
+    space.map(start, 4096 * 3, PageTableFlags::WRITABLE).unwrap();
+    assert_eq!(memory::free_frames(), free - 1 - 3 - 3);   // the level 4 table, the three tables under it and the pages
+    space.unmap(start, 4096).unwrap();
+    assert!(space.translate(start).is_none());
+    assert!(space.translate(start + 4096u64).is_some());
+    assert_eq!(memory::free_frames(), free - 1 - 3 - 2);
+
+    drop(space);
+    assert_eq!(memory::free_frames(), free);
+}
+
+#[test_case]
+fn drop_while_active() {
+    let (kernel, _) = Cr3::read();
+    let mut space = AddressSpace::new().unwrap();
+    space.map(VirtAddr::new(USER_START), 4096, PageTableFlags::WRITABLE).unwrap();
+    space.switch();
+    drop(space);                    // back on the kernel's table first
+    assert_eq!(Cr3::read().0, kernel);
+}
+
+#[panic_handler]
+fn panic(info: &PanicInfo) -> ! {
+    DerBo_OS::test_panic_handler(info)
+}
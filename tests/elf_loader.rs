@@ -0,0 +1,74 @@
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(DerBo_OS::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+
+extern crate alloc;
+
+use alloc::string::ToString;
+use alloc::vec;
+use bootloader::{entry_point, BootInfo};
+use core::panic::PanicInfo;
+use DerBo_OS::memory::USER_START;
+use DerBo_OS::process::{self, elf, programs};
+
+/* the ones of the tests, built on the host with their build.sh as the sample 'hello.elf' of 'exec' */
+static READONLY: &[u8] = include_bytes!("programs/readonly");
+
+entry_point!(main);
+
+fn main(boot_info: &'static BootInfo) -> ! {
+    use DerBo_OS::allocator;
+    use DerBo_OS::memory::{self, BootInfoFrameAllocator};
+    use DerBo_OS::task::thread;
+    use x86_64::VirtAddr;
+
+    DerBo_OS::init();
+    let phys_mem_offset = VirtAddr::new(boot_info.physical_memory_offset);
+    let mut mapper = unsafe { memory::init(phys_mem_offset) };
+    let mut frame_allocator = unsafe { BootInfoFrameAllocator::init(&boot_info.memory_map) };
+    allocator::init_heap(&mut mapper, &mut frame_allocator).expect("heap initialization failed");
+    memory::keep(mapper, frame_allocator);
+    thread::init();
+
+    test_main();
+    loop {}
+}
+
+#[test_case]
+fn parse_sample() {
+    let program = elf::parse(programs::find_executable("hello.elf").unwrap()).expect("the sample is a static ELF64 executable");
+    assert!(program.entry >= USER_START);
+    assert!(program.segments.iter().any(|segment| segment.writable && segment.data.is_empty()));   // its .bss
+}
+
+#[test_case]
+fn run_sample() {
+    let program = elf::parse(programs::find_executable("hello.elf").unwrap()).unwrap();
+    let args = vec!["hello".to_string(), "a".to_string(), "b".to_string()];
+    let env = vec!["USER=root".to_string()];
+    let loaded = process::load(&program, &args, &env).expect("the sample could not be loaded");
+    let pid = process::start(loaded, 0, None).expect("the sample could not be started");
+    while !process::finished(pid) {
+        x86_64::instructions::hlt();
+    }
+    assert_eq!(process::reap(pid), Some(3));   // it exits with argc
+}
+
+#[test_case]
+fn read_into_code_refused() {
+    let program = elf::parse(READONLY).unwrap();
+    assert!(program.segments.iter().all(|segment| !segment.writable));
+    let loaded = process::load(&program, &["readonly".to_string()], &[]).unwrap();
+    let pid = process::start(loaded, 0, None).unwrap();
+    while !process::finished(pid) {
+        x86_64::instructions::hlt();
+    }
+    assert_eq!(process::reap(pid), Some(-14));   // EFAULT, and the kernel is still here
+}
+
+#[panic_handler]
+fn panic(info: &PanicInfo) -> ! {
+    DerBo_OS::test_panic_handler(info)
+}
@@ -0,0 +1,155 @@
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(DerBo_OS::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+
+extern crate alloc;
+
+use alloc::string::{String, ToString};
+use alloc::vec;
+use bootloader::{entry_point, BootInfo};
+use core::arch::global_asm;
+use core::panic::PanicInfo;
+use DerBo_OS::buffer::terminal_buffer::TERMINAL_WRITERS;
+use DerBo_OS::process::{self, programs, Program};
+use x86_64::instructions::interrupts;
+
+entry_point!(main);
+
+fn main(boot_info: &'static BootInfo) -> ! {
+    use DerBo_OS::allocator;
+    use DerBo_OS::memory::{self, BootInfoFrameAllocator};
+    use DerBo_OS::task::thread;
+    use x86_64::VirtAddr;
+
+    DerBo_OS::init();
+    let phys_mem_offset = VirtAddr::new(boot_info.physical_memory_offset);
+    let mut mapper = unsafe { memory::init(phys_mem_offset) };
+    let mut frame_allocator = unsafe { BootInfoFrameAllocator::init(&boot_info.memory_map) };
+    allocator::init_heap(&mut mapper, &mut frame_allocator).expect("heap initialization failed");
+    memory::keep(mapper, frame_allocator);
+    thread::init();
+
+    test_main();
+    loop {}
+}
+
+/*
+Checks the system calls from ring 3: the registers kept, EFAULT for a pointer to the kernel
+and to unmapped memory, ENOSYS for an unknown call. Exits with 0, or the number of the check failed.
+*/
+global_asm!(
+    ".pushsection .rodata",
+    ".global TEST_ABI",
+    "TEST_ABI:",
+    "mov rbx, 0x1111",
+    "mov r12, 0x2222",
+    "mov r15, 0x3333",
+    "mov eax, 1",
+    "mov edi, 1",
+    "lea rsi, [rip + .Labi_text]",
+    "mov edx, 4",
+    "int 0x80",
+    "mov r13d, 1",
+    "cmp rax, 4",
+    "jne .Labi_fail",
+    "mov r13d, 2",
+    "cmp rbx, 0x1111",
+    "jne .Labi_fail",
+    "cmp r12, 0x2222",
+    "jne .Labi_fail",
+    "cmp r15, 0x3333",
+    "jne .Labi_fail",
+    "mov eax, 1",                   // the kernel's code
+    "mov edi, 1",
+    "mov rsi, 0x200000",
+    "mov edx, 8",
+    "int 0x80",
+    "mov r13d, 3",
+    "cmp rax, -14",
+    "jne .Labi_fail",
+    "mov eax, 1",                   // in the user part, but not mapped
+    "mov edi, 1",
+    "movabs rsi, 0x8000100000",
+    "mov edx, 8",
+    "int 0x80",
+    "mov r13d, 4",
+    "cmp rax, -14",
+    "jne .Labi_fail",
+    "mov eax, 99",
+    "int 0x80",
+    "mov r13d, 5",
+    "cmp rax, -38",
+    "jne .Labi_fail",
+    "xor r13d, r13d",
+    ".Labi_fail:",
+    "xor eax, eax",
+    "mov rdi, r13",
+    "int 0x80",
+    ".Labi_text:",
+    ".ascii \"abi\\n\"",
+    ".global TEST_ABI_END",
+    "TEST_ABI_END:",
+    ".popsection",
+);
+
+extern "C" {
+    static TEST_ABI: u8;
+    static TEST_ABI_END: u8;
+}
+
+fn run(image: &[u8], ticks: usize) -> Option<i64> {    // stopped after 'ticks' if it is still running
+    let args = vec!["test".to_string()];
+    let loaded = process::load(&Program::flat(image), &args, &[]).expect("the program could not be loaded");
+    let pid = process::start(loaded, 0, None).expect("the program could not be started");
+    let mut waited = 0;
+    while !process::finished(pid) {
+        if waited == ticks {
+            process::kill(pid);
+        }
+        x86_64::instructions::hlt();
+        waited += 1;
+    }
+    process::reap(pid)
+}
+
+fn screen_has(text: &str) -> bool {
+    interrupts::without_interrupts(|| {
+        let writer = TERMINAL_WRITERS[0].lock();
+        (0..writer.line_count()).any(|line| writer.line(line).iter().collect::<String>().contains(text))
+    })
+}
+
+#[test_case]
+fn hello() {
+    assert_eq!(run(programs::find("hello").unwrap(), usize::MAX), Some(0));
+    assert!(screen_has("Hello from ring 3!"));
+}
+
+#[test_case]
+fn system_calls() {
+    let image = unsafe {
+        let start = &TEST_ABI as *const u8;
+        core::slice::from_raw_parts(start, &TEST_ABI_END as *const u8 as usize - start as usize)
+    };
+    assert_eq!(run(image, usize::MAX), Some(0));
+}
+
+#[test_case]
+fn crash_stops_the_process_only() {
+    assert_eq!(run(programs::find("crash").unwrap(), usize::MAX), None);
+    assert!(screen_has("page fault, stopped"));
+    assert_eq!(run(programs::find("hello").unwrap(), usize::MAX), Some(0));
+}
+
+#[test_case]
+fn spin_is_killed() {
+    assert_eq!(run(programs::find("spin").unwrap(), 10), None);
+    assert_eq!(run(programs::find("hello").unwrap(), usize::MAX), Some(0));
+}
+
+#[panic_handler]
+fn panic(info: &PanicInfo) -> ! {
+    DerBo_OS::test_panic_handler(info)
+}
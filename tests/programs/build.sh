@@ -0,0 +1,11 @@
+#!/bin/sh
+# Build the programs of the ELF loader test with the host binutils, and commit the results.
+# They are linked in the user part of the address space, see memory::USER_START.
+set -e
+cd "$(dirname "$0")"
+for program in readonly; do
+    as --64 -o $program.o $program.s
+    ld -static -nostdlib -s --build-id=none -z noexecstack -z max-page-size=4096 \
+        -Ttext-segment=0x8000000000 -o $program $program.o
+    rm $program.o
+done
@@ -0,0 +1,16 @@
+# Asks the kernel to read into its own code, which is mapped read-only:
+# the call must fail with EFAULT, which becomes the exit status.
+
+    .intel_syntax noprefix
+    .globl _start
+
+    .text
+_start:
+    mov eax, 2                      # read(0, _start, 16)
+    xor edi, edi
+    lea rsi, [rip + _start]
+    mov edx, 16
+    int 0x80
+    mov rdi, rax                    # exit(the result)
+    xor eax, eax
+    int 0x80
@@ -0,0 +1,112 @@
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(DerBo_OS::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+
+extern crate alloc;
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use bootloader::{entry_point, BootInfo};
+use core::panic::PanicInfo;
+use core::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use DerBo_OS::allocator;
+use DerBo_OS::task::thread::{self, MAX_THREADS};
+use x86_64::instructions::{hlt, interrupts};
+
+entry_point!(main);
+
+fn main(boot_info: &'static BootInfo) -> ! {
+    use DerBo_OS::memory::{self, BootInfoFrameAllocator};
+    use x86_64::VirtAddr;
+
+    DerBo_OS::init();
+    let phys_mem_offset = VirtAddr::new(boot_info.physical_memory_offset);
+    let mut mapper = unsafe { memory::init(phys_mem_offset) };
+    let mut frame_allocator = unsafe { BootInfoFrameAllocator::init(&boot_info.memory_map) };
+    allocator::init_heap(&mut mapper, &mut frame_allocator).expect("heap initialization failed");
+    thread::init();
+
+    test_main();
+    loop {}
+}
+
+#[test_case]
+fn join_returns_the_result() {
+    let handle = thread::spawn_thread(None, || 6 * 7).unwrap();
+    assert_eq!(handle.join(), Some(42));
+}
+
+static STOP: AtomicBool = AtomicBool::new(false);
+static COUNTS: [AtomicU64; 2] = [AtomicU64::new(0), AtomicU64::new(0)];
+
+#[test_case]
+fn busy_threads_share_the_cpu() {
+    let busy = |i: usize| move || {
+        while !STOP.load(Ordering::SeqCst) {
+            COUNTS[i].fetch_add(1, Ordering::SeqCst);
+        }
+    };
+    let first = thread::spawn_thread(None, busy(0)).unwrap();
+    let second = thread::spawn_thread(None, busy(1)).unwrap();
+    for _ in 0..20 {                // about a second of timer ticks
+        hlt();
+    }
+    STOP.store(true, Ordering::SeqCst);
+    first.join();
+    second.join();
+    assert!(COUNTS[0].load(Ordering::SeqCst) > 0);
+    assert!(COUNTS[1].load(Ordering::SeqCst) > 0);
+}
+
+#[test_case]
+fn yield_gives_up_the_cpu() {
+    static RAN: AtomicBool = AtomicBool::new(false);
+    interrupts::without_interrupts(|| {     // no timer: only the yield lets the other thread run
+        let handle = thread::spawn_thread(None, || RAN.store(true, Ordering::SeqCst)).unwrap();
+        assert!(!RAN.load(Ordering::SeqCst));
+        thread::yield_now();
+        assert!(RAN.load(Ordering::SeqCst));
+        handle.join();
+    });
+}
+
+#[test_case]
+fn slots_and_stacks_are_reused() {
+    let round = || {
+        for i in 0..MAX_THREADS * 3 {
+            assert_eq!(thread::spawn_thread(None, move || i).unwrap().join(), Some(i));
+        }
+        allocator::heap_used()
+    };
+    let first = round();            // each round leaves its last thread to the next spawn
+    let second = round();
+    assert_eq!(first, second);
+    assert_eq!(thread::count(), 0);
+}
+
+#[test_case]
+fn heap_from_several_threads() {
+    let handles: Vec<_> = (0..4u64).map(|i| {
+        thread::spawn_thread(None, move || {
+            let mut sum = 0;
+            for j in 0..1000 {
+                let boxed = Box::new(i * 1000 + j);
+                let v: Vec<u64> = (0..(j % 16)).collect();
+                sum += *boxed + v.len() as u64;
+            }
+            sum
+        }).unwrap()
+    }).collect();
+    for (i, handle) in handles.into_iter().enumerate() {
+        let i = i as u64;
+        let expected: u64 = (0..1000).map(|j| i * 1000 + j + j % 16).sum();
+        assert_eq!(handle.join(), Some(expected));
+    }
+}
+
+#[panic_handler]
+fn panic(info: &PanicInfo) -> ! {
+    DerBo_OS::test_panic_handler(info)
+}